@@ -2,7 +2,7 @@ use std::{io::{Read, Seek}, path::PathBuf};
 
 use eframe::egui;
 
-use crate::{files::{self, generic_file}, traits::*};
+use crate::{data_export::DataFormat, files, jobs::Jobs, texture::batch, traits::*, widgets::{batch_export_dialog::BatchExportDialog, dictionary_window::DictionaryWindow, index_diff_window::IndexDiffWindow, texture_compare::TextureCompareWindow}};
 
 pub struct ReplicantToolkit {
     runtime: tokio::runtime::Runtime,
@@ -13,6 +13,12 @@ pub struct ReplicantToolkit {
     files_to_close: Vec<usize>,
 
     top_layer_id: Option<egui::LayerId>,
+
+    jobs: Jobs,
+    batch_export_dialog: BatchExportDialog,
+    texture_compare_window: TextureCompareWindow,
+    dictionary_window: DictionaryWindow,
+    index_diff_window: IndexDiffWindow,
 }
 
 impl Default for ReplicantToolkit {
@@ -28,6 +34,12 @@ impl Default for ReplicantToolkit {
             selected_file_indices: Vec::new(),
             files_to_close: Vec::new(),
             top_layer_id: None,
+
+            jobs: Jobs::default(),
+            batch_export_dialog: BatchExportDialog::default(),
+            texture_compare_window: TextureCompareWindow::default(),
+            dictionary_window: DictionaryWindow::default(),
+            index_diff_window: IndexDiffWindow::default(),
         }
     }
 }
@@ -38,44 +50,52 @@ impl ReplicantToolkit {
             return;
         }
 
-        let mut file_stream = std::fs::File::open(path.clone()).expect("Failed to open file");
-        let mut file_magic = [0; 4];
-        file_stream.read_exact(&mut file_magic).expect("Failed to read magic");
-        file_stream.seek(std::io::SeekFrom::Start(0)).expect("Failed to seek");
-
-        match &file_magic {
-            [0x28, 0xB5, 0x2F, 0xFD] => {
-                let zstd_file = match files::zstd::ZstdManager::new(path.clone(), self.runtime.handle().clone(), file_stream) {
-                    Ok(zstd_file) => zstd_file,
-                    Err(e) => {
-                        self.toasts.error(format!("Failed to open file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                        return;
-                    }
-                };
-                self.open_files.push(Box::new(zstd_file));
-            },
-            b"PACK" => {
-                let pack_file = match files::pack::PackManager::new(path.clone(), self.runtime.handle().clone(), file_stream) {
-                    Ok(pack_file) => pack_file,
-                    Err(e) => {
-                        self.toasts.error(format!("Failed to open file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                        return;
-                    }
-                };
-                self.open_files.push(Box::new(pack_file));
-            },
-            _ => {
-                let generic_file = Box::new(generic_file::GenericFileManager::new(path.clone()));
-                self.open_files.push(generic_file);
+        let file_stream = match std::fs::File::open(path.clone()) {
+            Ok(file_stream) => file_stream,
+            Err(e) => {
+                self.toasts.error(format!("Failed to open file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                return;
             }
         };
-        
+        self.open_reader(path, file_stream);
+    }
+
+    // Opens a file that only exists in memory, e.g. an entry read out of an archive
+    fn open_virtual_file(&mut self, path: PathBuf, data: Vec<u8>) {
+        if self.open_files.iter().any(|file| file.path() == &path) {
+            return;
+        }
+
+        self.open_reader(path, std::io::Cursor::new(data));
+    }
+
+    fn open_reader<R: Read + Seek>(&mut self, path: PathBuf, file_stream: R) {
+        match files::open(path.clone(), self.runtime.handle().clone(), file_stream) {
+            Ok(file) => self.open_files.push(file),
+            Err(e) => {
+                self.toasts.error(format!("Failed to open file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                return;
+            }
+        };
+
         self.open_files.sort_by(|a, b| a.path().cmp(b.path()));
         // Find the index of the newly opened file
         let index = self.open_files.iter().position(|file| file.path() == &path).unwrap();
         self.selected_file_indices = vec![index];
     }
 
+    fn open_pending_files(&mut self) {
+        let pending_opens = self.open_files.iter_mut().flat_map(|file| file.take_pending_opens()).collect::<Vec<_>>();
+        for (path, file) in pending_opens {
+            match file {
+                Ok(data) => self.open_virtual_file(path, data),
+                Err(e) => {
+                    self.toasts.error(format!("Failed to open {}: {}", path.to_str().unwrap_or_default(), e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                }
+            }
+        }
+    }
+
     fn close_file(&mut self, index: usize) {
         self.selected_file_indices.retain(|i| *i != index);
 
@@ -121,6 +141,42 @@ impl ReplicantToolkit {
         }
     }
 
+    fn export_folder_textures(&mut self, options: batch::BatchExportOptions) {
+        let Some(input_folder) = rfd::FileDialog::new().set_title("Folder to export textures from").pick_folder() else {
+            return;
+        };
+        let Some(output_folder) = rfd::FileDialog::new().set_title("Export textures").pick_folder() else {
+            return;
+        };
+
+        let job = self.jobs.start(format!("Exporting textures from {}", input_folder.to_str().unwrap_or_default()), 1);
+        let runtime = self.runtime.handle().clone();
+        self.runtime.spawn_blocking(move || {
+            batch::export_folder_textures(runtime, input_folder, output_folder, options, job);
+        });
+    }
+
+    fn export_data(&mut self, index: usize, format: DataFormat) {
+        let file = &self.open_files[index];
+        let stem = file.path().file_stem().unwrap_or_default().to_str().unwrap_or_default().replace('!', "_");
+        let Some(path) = rfd::FileDialog::new().set_title(format!("Export {}", format.name())).add_filter(format.name(), &[format.extension()]).set_file_name(format!("{}.{}", stem, format.extension())).save_file() else {
+            return;
+        };
+
+        let result = file.data_export().and_then(|data_export| match data_export {
+            Some(data_export) => data_export.write(&path, format),
+            None => Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "This file has no parsed data to export.")),
+        });
+        match result {
+            Ok(count) => {
+                self.toasts.success(format!("Exported {} {} file(s).", count, format.name())).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            },
+            Err(e) => {
+                self.toasts.error(format!("Failed to export data: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
     fn get_index_of_top_layer_id(&self) -> Option<usize> {
         let mut index = 0;
         if let Some(top_layer_id) = self.top_layer_id {
@@ -138,6 +194,7 @@ impl ReplicantToolkit {
 impl eframe::App for ReplicantToolkit {
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
         self.close_queued_files();
+        self.open_pending_files();
         self.toasts.show(ctx);
         egui_extras::install_image_loaders(ctx);
 
@@ -163,12 +220,54 @@ impl eframe::App for ReplicantToolkit {
                             ui.close_menu();
                         }
 
+                        ui.separator();
+
+                        if ui.button("Export textures from folder…").clicked() {
+                            self.batch_export_dialog.open();
+                            ui.close_menu();
+                        }
+
+                        let top_index = self.get_index_of_top_layer_id();
+                        ui.add_enabled_ui(top_index.is_some(), |ui| {
+                            ui.menu_button("Export data", |ui| {
+                                for format in DataFormat::ALL {
+                                    let label = match format {
+                                        DataFormat::Csv => "CSV tables…".to_string(),
+                                        _ => format!("{}…", format.name()),
+                                    };
+                                    if ui.button(label).clicked() && let Some(index) = top_index {
+                                        self.export_data(index, format);
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        });
+
+                        ui.separator();
+
                         if ui.button("Close all").clicked() {
                             self.close_all_files();
                             ui.close_menu();
                         }
                     });
 
+                    ui.menu_button(format!("{} Tools", egui_phosphor::regular::WRENCH), |ui| {
+                        if ui.button("Compare textures…").clicked() {
+                            self.texture_compare_window.open();
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Name dictionary…").clicked() {
+                            self.dictionary_window.open();
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Compare indexes…").clicked() {
+                            self.index_diff_window.open();
+                            ui.close_menu();
+                        }
+                    });
+
                     if let Some(index) = self.get_index_of_top_layer_id() {
                         self.open_files[index].paint_top_bar(ui, &mut self.toasts);
                     }
@@ -280,6 +379,14 @@ impl eframe::App for ReplicantToolkit {
                 for file in self.open_files.iter_mut() {
                     file.paint_floating(ui, &mut self.toasts);
                 }
+
+                if let Some(options) = self.batch_export_dialog.show(ui.ctx(), egui::Id::new("folder_batch_export"), "Every PACK in a folder") {
+                    self.export_folder_textures(options);
+                }
+                self.texture_compare_window.show(ui.ctx());
+                self.dictionary_window.show(ui.ctx(), &mut self.toasts);
+                self.index_diff_window.show(ui.ctx(), self.runtime.handle(), &mut self.toasts);
+                self.jobs.paint(ui.ctx());
             });
     }
 }
\ No newline at end of file
@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+
+use crate::data_export::{DataExport, DataFormat};
+use crate::extract::{self, ExtractOptions, OverwritePolicy};
+use crate::filter::Filter;
+use crate::index_diff::{self, ChangeKind, IndexDiff, Verification};
+use crate::index_document::IndexDocument;
+use crate::manifest::Manifest;
+use crate::vfs::Vfs;
+
+#[derive(clap::Parser)]
+#[command(version, about = "NieR Replicant ver.1.2247... Toolkit, runs the GUI when no command is given")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Extract files listed in an info.arc index
+    Extract {
+        /// Path to info.arc, the archives are expected next to it
+        index: PathBuf,
+        /// Folder to extract into
+        output: PathBuf,
+        /// Only extract files matching this filter, e.g. "ext:.pack size>1MB"
+        #[arg(short, long)]
+        filter: Option<String>,
+        /// What to do with files that already exist
+        #[arg(long, value_enum, default_value_t = OverwritePolicy::Overwrite)]
+        overwrite: OverwritePolicy,
+        /// Only print what would be written
+        #[arg(long)]
+        dry_run: bool,
+        /// Extract everything even if the manifest says it is up to date
+        #[arg(long)]
+        full: bool,
+    },
+    /// Compare two info.arc indexes, e.g. from before and after a patch
+    Diff {
+        /// The older info.arc
+        old: PathBuf,
+        /// The newer info.arc
+        new: PathBuf,
+        #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+        /// Only report these kinds of changes
+        #[arg(short, long, value_enum, value_delimiter = ',')]
+        kind: Vec<ChangeKind>,
+        /// Read moved files from both installs to confirm their data is the same
+        #[arg(long)]
+        verify: bool,
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the parsed structures of a PACK, BXON or zstd file
+    Export {
+        /// The file to read
+        input: PathBuf,
+        #[arg(long, value_enum, default_value_t = DataFormat::Json)]
+        format: DataFormat,
+        /// Only export this table, e.g. "files"
+        #[arg(short, long)]
+        table: Option<String>,
+        /// Write to this file instead of stdout, CSV writes one "<name>_<table>.csv" per table next to it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write an info.arc as an editable JSON document
+    ExportIndex {
+        /// The info.arc to read
+        index: PathBuf,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Rebuild an info.arc from a JSON document written by export-index
+    BuildIndex {
+        /// The JSON document
+        document: PathBuf,
+        /// Where to write the info.arc
+        output: PathBuf,
+    },
+    /// Write every asset and file of a PACK as standalone files with a manifest
+    SplitPack {
+        /// The PACK to split
+        pack: PathBuf,
+        /// Folder to write into
+        output: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+pub fn run(command: Command) -> Result<(), std::io::Error> {
+    match command {
+        Command::Extract { index, output, filter, overwrite, dry_run, full } => extract(index, output, filter.as_deref().unwrap_or_default(), ExtractOptions { overwrite, dry_run, incremental: !full }),
+        Command::Diff { old, new, format, kind, verify, output } => diff(old, new, format, &kind, verify, output),
+        Command::Export { input, format, table, output } => export(input, format, table.as_deref(), output),
+        Command::ExportIndex { index, output } => export_index(index, output),
+        Command::BuildIndex { document, output } => build_index(document, output),
+        Command::SplitPack { pack, output } => split_pack(pack, output),
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<Filter, std::io::Error> {
+    Filter::parse(filter).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+}
+
+fn extract(index: PathBuf, output: PathBuf, filter: &str, options: ExtractOptions) -> Result<(), std::io::Error> {
+    let filter = parse_filter(filter)?;
+    let vfs = Vfs::mount(&index)?;
+
+    let manifest = Manifest::open(&output)?;
+    let (current, mut entries): (Vec<_>, Vec<_>) = vfs.entries().filter(|metadata| filter.matches(&metadata.filter_entry())).partition(|metadata| options.incremental && manifest.is_current(&output, &metadata.manifest_entry()));
+    // In archive order, so each archive is only decompressed once
+    entries.sort_by(|a, b| (&a.archive, a.offset).cmp(&(&b.archive, b.offset)));
+    if options.dry_run {
+        for metadata in current.iter() {
+            println!("Up to date: {}", metadata.path);
+        }
+        for metadata in entries.iter() {
+            match extract::plan(&output, &metadata.path, metadata.extracted_size, options.overwrite) {
+                Ok(outcome) => println!("{}", outcome.describe()),
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&output)?;
+    let required = entries.iter().filter(|metadata| extract::plan(&output, &metadata.path, metadata.extracted_size, options.overwrite).is_ok_and(|outcome| outcome.writes())).map(|metadata| metadata.extracted_size).sum();
+    extract::check_free_space(&output, required)?;
+
+    let mut failed = 0;
+    for (index, metadata) in entries.iter().enumerate() {
+        let result = extract::sanitize_path(&metadata.path)
+            .and_then(|_| vfs.read(&metadata.path))
+            .and_then(|data| extract::write_file(&output, &metadata.path, &data, options))
+            .and_then(|outcome| {
+                if outcome.is_in_place() {
+                    manifest.record(&metadata.manifest_entry())?;
+                }
+                Ok(outcome)
+            });
+        match result {
+            Ok(outcome) => println!("[{}/{}] {}", index + 1, entries.len(), outcome.describe()),
+            Err(e) => {
+                eprintln!("[{}/{}] Failed to extract {}: {}", index + 1, entries.len(), metadata.path, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("Extracted {} of {} files, {} were already up to date.", entries.len() - failed, entries.len(), current.len());
+    match failed {
+        0 => Ok(()),
+        _ => Err(std::io::Error::other(format!("{} files failed to extract.", failed))),
+    }
+}
+
+fn diff(old: PathBuf, new: PathBuf, format: DiffFormat, kinds: &[ChangeKind], verify: bool, output: Option<PathBuf>) -> Result<(), std::io::Error> {
+    let (old, new) = (Vfs::mount(&old)?, Vfs::mount(&new)?);
+    let mut diff = IndexDiff::compare(&old, &new);
+    if verify {
+        diff.verify_moved(&old, &new);
+    }
+    let changes = diff.changes.iter().filter(|change| kinds.is_empty() || kinds.contains(&change.kind)).collect::<Vec<_>>();
+    for duplicate in diff.duplicates.iter() {
+        eprintln!("Skipped duplicate hash {:08X} in the {} index: {}", duplicate.metadata.hash, duplicate.index, duplicate.metadata.path);
+    }
+
+    let report = match format {
+        DiffFormat::Text => {
+            let mut report = changes.iter().map(|change| match change.verification {
+                Verification::Unverified => format!("{:<8} {:08X} {} (unverified)\n", change.kind.name(), change.hash, change.path),
+                _ => format!("{:<8} {:08X} {}\n", change.kind.name(), change.hash, change.path),
+            }).collect::<String>();
+            report.push_str(&format!("{}\n", diff.summary()));
+            report
+        },
+        DiffFormat::Json => index_diff::to_json(&changes)?,
+        DiffFormat::Csv => index_diff::to_csv(&changes),
+    };
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, report)?;
+            eprintln!("{}", diff.summary());
+            Ok(())
+        },
+        None => {
+            print!("{}", report);
+            Ok(())
+        }
+    }
+}
+
+fn export(input: PathBuf, format: DataFormat, table: Option<&str>, output: Option<PathBuf>) -> Result<(), std::io::Error> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    let file = crate::files::open(input.clone(), runtime.handle().clone(), std::fs::File::open(&input)?)?;
+    let Some(mut data_export) = file.data_export()? else {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("{} has no parsed data to export.", input.to_str().unwrap_or_default())));
+    };
+    let table_names = data_export.tables.iter().map(|table| table.name).collect::<Vec<_>>().join(", ");
+
+    // A single table is exported on its own, as rows for JSON and YAML
+    if let Some(name) = table {
+        let index = data_export.tables.iter().position(|table| table.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("No table named \"{}\", available: {}.", name, table_names)))?;
+        let table = data_export.tables.swap_remove(index);
+        data_export = DataExport {
+            document: serde_json::Value::Array(table.rows.clone()),
+            tables: vec![table],
+        };
+    }
+
+    let report = match (format, output) {
+        (DataFormat::Csv, Some(output)) => {
+            let count = data_export.write(&output, format)?;
+            eprintln!("Wrote {} CSV files.", count);
+            return Ok(());
+        },
+        (DataFormat::Csv, None) => match data_export.tables.as_slice() {
+            [table] => table.to_csv(),
+            _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("CSV to stdout needs --table, available: {}.", table_names))),
+        },
+        (_, Some(output)) => {
+            data_export.write(&output, format)?;
+            return Ok(());
+        },
+        (_, None) => data_export.to_string(format)?,
+    };
+    print!("{}", report);
+    Ok(())
+}
+
+fn export_index(index: PathBuf, output: Option<PathBuf>) -> Result<(), std::io::Error> {
+    let (document, warnings) = IndexDocument::export(&std::fs::read(&index)?)?;
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let json = serde_json::to_string_pretty(&document).map_err(std::io::Error::other)?;
+    match output {
+        Some(output) => std::fs::write(output, json),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+fn build_index(document: PathBuf, output: PathBuf) -> Result<(), std::io::Error> {
+    let document = serde_json::from_str::<IndexDocument>(&std::fs::read_to_string(&document)?).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let (data, warnings) = document.build()?;
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    std::fs::write(&output, data)?;
+    eprintln!("Wrote {} archives and {} files to {}.", document.archives.len(), document.files.len(), output.to_str().unwrap_or_default());
+    Ok(())
+}
+
+fn split_pack(pack: PathBuf, output: PathBuf) -> Result<(), std::io::Error> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    let pack_manager = crate::files::pack::PackManager::new(pack.clone(), runtime.handle().clone(), std::fs::File::open(&pack)?)?;
+    let count = pack_manager.split(&output)?;
+    eprintln!("Wrote {} entries to {}.", count, output.to_str().unwrap_or_default());
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+    Csv,
+}
+
+impl DataFormat {
+    pub const ALL: [DataFormat; 3] = [DataFormat::Json, DataFormat::Yaml, DataFormat::Csv];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataFormat::Json => "JSON",
+            DataFormat::Yaml => "YAML",
+            DataFormat::Csv => "CSV",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DataFormat::Json => "json",
+            DataFormat::Yaml => "yaml",
+            DataFormat::Csv => "csv",
+        }
+    }
+}
+
+// One flat row per entry, e.g. the files of a PACK
+pub struct DataTable {
+    pub name: &'static str,
+    pub rows: Vec<Value>,
+}
+
+// Everything parsed from a file: the whole structure for JSON and YAML, its lists as tables for CSV
+pub struct DataExport {
+    pub document: Value,
+    pub tables: Vec<DataTable>,
+}
+
+fn to_value(value: &impl serde::Serialize) -> Result<Value, std::io::Error> {
+    serde_json::to_value(value).map_err(std::io::Error::other)
+}
+
+impl DataExport {
+    pub fn new(document: &impl serde::Serialize) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            document: to_value(document)?,
+            tables: Vec::new(),
+        })
+    }
+
+    pub fn with_table<T: serde::Serialize>(mut self, name: &'static str, rows: impl Iterator<Item = T>) -> Result<Self, std::io::Error> {
+        let rows = rows.map(|row| to_value(&row)).collect::<Result<Vec<_>, _>>()?;
+        self.tables.push(DataTable { name, rows });
+        Ok(self)
+    }
+
+    pub fn to_string(&self, format: DataFormat) -> Result<String, std::io::Error> {
+        match format {
+            DataFormat::Json => serde_json::to_string_pretty(&self.document).map_err(std::io::Error::other),
+            DataFormat::Yaml => serde_yaml::to_string(&self.document).map_err(std::io::Error::other),
+            DataFormat::Csv => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "CSV exports one table at a time.")),
+        }
+    }
+
+    // JSON and YAML go to `path`, CSV writes one "<stem>_<table>.csv" per table next to it
+    pub fn write(&self, path: &Path, format: DataFormat) -> Result<usize, std::io::Error> {
+        if format != DataFormat::Csv {
+            std::fs::write(path, self.to_string(format)?)?;
+            return Ok(1);
+        }
+
+        let stem = path.file_stem().unwrap_or_default().to_str().unwrap_or_default();
+        for table in self.tables.iter() {
+            std::fs::write(path.with_file_name(format!("{}_{}.csv", stem, table.name)), table.to_csv())?;
+        }
+        Ok(self.tables.len())
+    }
+}
+
+// Puts `name: value` first in an object, e.g. to say which file a nested entry came from
+pub fn with_field(name: &str, value: &str, row: Value) -> Value {
+    let Value::Object(fields) = row else {
+        return row;
+    };
+    let mut object = serde_json::Map::new();
+    object.insert(name.to_string(), Value::String(value.to_string()));
+    object.extend(fields);
+    Value::Object(object)
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => crate::util::csv_field(string),
+        // Nested values are kept as JSON in a single cell
+        _ => crate::util::csv_field(&value.to_string()),
+    }
+}
+
+impl DataTable {
+    // Columns come from the first row, every row of a table has the same fields
+    pub fn to_csv(&self) -> String {
+        let Some(Value::Object(first)) = self.rows.first() else {
+            return String::new();
+        };
+        let columns = first.keys().cloned().collect::<Vec<_>>();
+
+        let mut csv = columns.iter().map(|column| crate::util::csv_field(column)).collect::<Vec<_>>().join(",");
+        csv.push('\n');
+        for row in self.rows.iter() {
+            let cells = columns.iter().map(|column| row.get(column).map(csv_value).unwrap_or_default()).collect::<Vec<_>>();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+// Four character magic numbers read as text, e.g. "PACK"
+pub fn serialize_magic<S: serde::Serializer>(magic: &[u8; 4], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&String::from_utf8_lossy(magic))
+}
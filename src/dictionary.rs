@@ -0,0 +1,86 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufRead, Write};
+use std::sync::{LazyLock, RwLock};
+
+use crate::hash;
+
+// Known paths keyed by their hash, shared by every manager so names seen in one file resolve hashes in another
+#[derive(Default)]
+struct NameDictionary {
+    names: HashMap<u32, String>,
+    // Hashes that were seen without a matching name
+    seen: BTreeSet<u32>,
+}
+
+static DICTIONARY: LazyLock<RwLock<NameDictionary>> = LazyLock::new(|| RwLock::new(NameDictionary::default()));
+
+pub fn add(name: &str) {
+    if name.is_empty() {
+        return;
+    }
+    let hash = hash::hash_path(name);
+    DICTIONARY.write().unwrap().names.entry(hash).or_insert_with(|| name.to_string());
+}
+
+// Records a stored name and hash, the hash stays unresolved until some name hashes to it
+pub fn observe(name: &str, hash: u32) {
+    add(name);
+    if name.is_empty() || !hash::verify(name, hash) {
+        DICTIONARY.write().unwrap().seen.insert(hash);
+    }
+}
+
+pub fn resolve(hash: u32) -> Option<String> {
+    DICTIONARY.read().unwrap().names.get(&hash).cloned()
+}
+
+// The stored name when present, otherwise whatever the dictionary knows about the hash
+pub fn display_name(name: &str, hash: u32) -> String {
+    match name.is_empty() {
+        true => resolve(hash).unwrap_or_else(|| format!("{:08X}", hash)),
+        false => name.to_string(),
+    }
+}
+
+pub fn len() -> usize {
+    DICTIONARY.read().unwrap().names.len()
+}
+
+pub fn unresolved() -> Vec<u32> {
+    let dictionary = DICTIONARY.read().unwrap();
+    dictionary.seen.iter().filter(|hash| !dictionary.names.contains_key(hash)).copied().collect()
+}
+
+// One path per line, lines starting with '#' are ignored. Returns the number of new names.
+pub fn load(path: &std::path::Path) -> Result<usize, std::io::Error> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let before = len();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        add(line);
+    }
+    Ok(len() - before)
+}
+
+pub fn save(path: &std::path::Path) -> Result<(), std::io::Error> {
+    let mut names = DICTIONARY.read().unwrap().names.values().cloned().collect::<Vec<_>>();
+    names.sort();
+
+    let mut output_file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for name in names {
+        writeln!(output_file, "{}", name)?;
+    }
+    output_file.flush()
+}
+
+pub fn save_unresolved(path: &std::path::Path) -> Result<(), std::io::Error> {
+    let mut output_file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for hash in unresolved() {
+        writeln!(output_file, "{:08X}", hash)?;
+    }
+    output_file.flush()
+}
@@ -0,0 +1,237 @@
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum OverwritePolicy {
+    #[default]
+    Overwrite,
+    Skip,
+    // Writes next to the existing file as "name (1).ext"
+    Rename,
+    // Only writes when the size or contents differ
+    IfDifferent,
+}
+
+impl OverwritePolicy {
+    pub const ALL: [OverwritePolicy; 4] = [OverwritePolicy::Overwrite, OverwritePolicy::Skip, OverwritePolicy::Rename, OverwritePolicy::IfDifferent];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverwritePolicy::Overwrite => "Overwrite",
+            OverwritePolicy::Skip => "Skip existing",
+            OverwritePolicy::Rename => "Rename",
+            OverwritePolicy::IfDifferent => "Only if different",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ExtractOptions {
+    pub overwrite: OverwritePolicy,
+    pub dry_run: bool,
+    // Skip files the manifest in the output folder says are already up to date
+    pub incremental: bool,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            overwrite: OverwritePolicy::default(),
+            dry_run: false,
+            incremental: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum WriteOutcome {
+    Created(PathBuf),
+    Overwritten(PathBuf),
+    Renamed(PathBuf),
+    Skipped(PathBuf),
+    Unchanged(PathBuf),
+}
+
+impl WriteOutcome {
+    pub fn path(&self) -> &Path {
+        match self {
+            WriteOutcome::Created(path) | WriteOutcome::Overwritten(path) | WriteOutcome::Renamed(path) | WriteOutcome::Skipped(path) | WriteOutcome::Unchanged(path) => path,
+        }
+    }
+
+    pub fn writes(&self) -> bool {
+        matches!(self, WriteOutcome::Created(_) | WriteOutcome::Overwritten(_) | WriteOutcome::Renamed(_))
+    }
+
+    // Whether the file at the original path now holds the extracted data
+    pub fn is_in_place(&self) -> bool {
+        matches!(self, WriteOutcome::Created(_) | WriteOutcome::Overwritten(_) | WriteOutcome::Unchanged(_))
+    }
+
+    pub fn describe(&self) -> String {
+        let action = match self {
+            WriteOutcome::Created(_) => "Create",
+            WriteOutcome::Overwritten(_) => "Overwrite",
+            WriteOutcome::Renamed(_) => "Write renamed",
+            WriteOutcome::Skipped(_) => "Skip existing",
+            WriteOutcome::Unchanged(_) => "Unchanged",
+        };
+        format!("{}: {}", action, self.path().to_str().unwrap_or_default())
+    }
+}
+
+const INVALID_CHARACTERS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+const RESERVED_NAMES: [&str; 22] = ["CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"];
+
+fn invalid_path(name: &str, reason: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Refusing to extract \"{}\": {}.", name, reason))
+}
+
+// Turns an archive path into a relative path that cannot leave the output folder
+pub fn sanitize_path(name: &str) -> Result<PathBuf, std::io::Error> {
+    let name_slashes = name.replace('\\', "/");
+    if name_slashes.starts_with('/') || Path::new(&name_slashes).has_root() || name_slashes.chars().nth(1) == Some(':') {
+        return Err(invalid_path(name, "absolute path"));
+    }
+
+    let mut path = PathBuf::new();
+    for segment in name_slashes.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => return Err(invalid_path(name, "parent directory segment")),
+            _ => {}
+        }
+        if segment.chars().any(|c| c.is_control() || INVALID_CHARACTERS.contains(&c)) {
+            return Err(invalid_path(name, "invalid characters"));
+        }
+        if segment.ends_with(['.', ' ']) {
+            return Err(invalid_path(name, "segment ends with a dot or space"));
+        }
+        let stem = segment.split('.').next().unwrap_or_default();
+        if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+            return Err(invalid_path(name, "reserved file name"));
+        }
+        path.push(segment);
+    }
+
+    if path.as_os_str().is_empty() {
+        return Err(invalid_path(name, "empty path"));
+    }
+    // Belt and braces, nothing above should let these through
+    if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(invalid_path(name, "not a plain relative path"));
+    }
+    Ok(path)
+}
+
+// "name.ext" -> "name (1).ext", "name (2).ext", … until one does not exist
+fn free_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_str().unwrap_or_default().to_string();
+    let extension = path.extension().map(|extension| format!(".{}", extension.to_str().unwrap_or_default())).unwrap_or_default();
+    (1..).map(|index| path.with_file_name(format!("{} ({}){}", stem, index, extension))).find(|candidate| !candidate.exists()).unwrap()
+}
+
+fn same_contents(path: &Path, data: &[u8]) -> Result<bool, std::io::Error> {
+    if std::fs::metadata(path)?.len() != data.len() as u64 {
+        return Ok(false);
+    }
+    let mut existing = Vec::with_capacity(data.len());
+    std::fs::File::open(path)?.read_to_end(&mut existing)?;
+    Ok(existing == data)
+}
+
+// Decides what writing `size` bytes to `name` would do without reading the file itself.
+// Only-if-different can only compare sizes here, equal sizes are reported as unchanged.
+pub fn plan(output_folder: &Path, name: &str, size: u64, policy: OverwritePolicy) -> Result<WriteOutcome, std::io::Error> {
+    let path = output_folder.join(sanitize_path(name)?);
+    if !path.exists() {
+        return Ok(WriteOutcome::Created(path));
+    }
+    Ok(match policy {
+        OverwritePolicy::Overwrite => WriteOutcome::Overwritten(path),
+        OverwritePolicy::Skip => WriteOutcome::Skipped(path),
+        OverwritePolicy::Rename => WriteOutcome::Renamed(free_path(&path)),
+        OverwritePolicy::IfDifferent => match std::fs::metadata(&path)?.len() == size {
+            true => WriteOutcome::Unchanged(path),
+            false => WriteOutcome::Overwritten(path),
+        },
+    })
+}
+
+pub fn write_file(output_folder: &Path, name: &str, data: &[u8], options: ExtractOptions) -> Result<WriteOutcome, std::io::Error> {
+    let mut outcome = plan(output_folder, name, data.len() as u64, options.overwrite)?;
+    if let WriteOutcome::Unchanged(path) = &outcome && !same_contents(path, data)? {
+        outcome = WriteOutcome::Overwritten(path.clone());
+    }
+    if options.dry_run || !outcome.writes() {
+        return Ok(outcome);
+    }
+
+    let output_path = outcome.path();
+    if let Some(output_dir) = output_path.parent() {
+        std::fs::create_dir_all(output_dir)?;
+    }
+    let mut output_file = std::fs::File::create(output_path)?;
+    output_file.write_all(data)?;
+    output_file.flush()?;
+    Ok(outcome)
+}
+
+// Fails early when the files would not fit, sizes of files that already exist are still counted
+pub fn check_free_space(output_folder: &Path, required: u64) -> Result<(), std::io::Error> {
+    let available = fs4::available_space(output_folder)?;
+    match required > available {
+        true => Err(std::io::Error::new(std::io::ErrorKind::StorageFull, format!("Extraction needs {} but only {} is free.", crate::util::format_size(required), crate::util::format_size(available)))),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_path_keeps_relative_paths() {
+        assert_eq!(sanitize_path("core/title.pack").unwrap(), PathBuf::from("core").join("title.pack"));
+        assert_eq!(sanitize_path("core\\./title.pack").unwrap(), PathBuf::from("core").join("title.pack"));
+        assert_eq!(sanitize_path("CONSOLE.pack").unwrap(), PathBuf::from("CONSOLE.pack"));
+    }
+
+    #[test]
+    fn sanitize_path_rejects_parent_segments() {
+        assert!(sanitize_path("..").is_err());
+        assert!(sanitize_path("core/../../title.pack").is_err());
+        assert!(sanitize_path("..\\title.pack").is_err());
+    }
+
+    #[test]
+    fn sanitize_path_rejects_absolute_paths() {
+        assert!(sanitize_path("/etc/passwd").is_err());
+        assert!(sanitize_path("\\Windows\\win.ini").is_err());
+        assert!(sanitize_path("C:/Windows/win.ini").is_err());
+        assert!(sanitize_path("c:title.pack").is_err());
+    }
+
+    #[test]
+    fn sanitize_path_rejects_reserved_names() {
+        assert!(sanitize_path("CON").is_err());
+        assert!(sanitize_path("core/nul.txt").is_err());
+        assert!(sanitize_path("Com1.pack").is_err());
+        assert!(sanitize_path("lpt9").is_err());
+    }
+
+    #[test]
+    fn sanitize_path_rejects_trailing_dot_or_space() {
+        assert!(sanitize_path("core/title.").is_err());
+        assert!(sanitize_path("core /title.pack").is_err());
+        assert!(sanitize_path("title.pack ").is_err());
+    }
+
+    #[test]
+    fn sanitize_path_rejects_invalid_characters_and_empty_paths() {
+        assert!(sanitize_path("core/ti?tle.pack").is_err());
+        assert!(sanitize_path("core/title\0.pack").is_err());
+        assert!(sanitize_path("").is_err());
+        assert!(sanitize_path("./").is_err());
+    }
+}
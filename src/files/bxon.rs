@@ -3,21 +3,25 @@ use std::path::PathBuf;
 use byteorder::ReadBytesExt;
 use eframe::egui;
 
+use crate::data_export::DataExport;
 use crate::files::tp_archive_file_param::TpArchiveFileParamManager;
 use crate::traits::*;
 use crate::util::ReadUtilExt;
+use crate::widgets::hex_viewer::HexViewer;
 
 use super::tp_gx_tex_head::TpGxTexHeadManager;
 
-struct Bxon {
+#[derive(serde::Serialize)]
+pub(crate) struct Bxon {
+    #[serde(serialize_with = "crate::data_export::serialize_magic")]
     id: [u8; 4],
-    version: u32,
-    project_id: u32,
+    pub(crate) version: u32,
+    pub(crate) project_id: u32,
     relative_offset_asset_type: u32,
-    offset_asset_type: u64,
+    pub(crate) offset_asset_type: u64,
     relative_offset_asset_data: u32,
-    offset_asset_data: u64,
-    asset_type: String,
+    pub(crate) offset_asset_data: u64,
+    pub(crate) asset_type: String,
 }
 
 impl Bxon {
@@ -56,7 +60,13 @@ pub struct BxonManager {
 }
 
 impl BxonManager {
-    pub fn new<R: Read + Seek>(path: PathBuf, runtime: tokio::runtime::Handle, mut reader: R) -> Result<Self, std::io::Error> {
+    pub fn new<R: Read + Seek>(path: PathBuf, runtime: tokio::runtime::Handle, reader: R) -> Result<Self, std::io::Error> {
+        Self::with_size(path, runtime, reader, None)
+    }
+
+    // `size` bounds the BXON block when it is embedded in a larger reader such as a PACK, otherwise it runs to the end
+    pub fn with_size<R: Read + Seek>(path: PathBuf, runtime: tokio::runtime::Handle, mut reader: R, size: Option<u64>) -> Result<Self, std::io::Error> {
+        let start = reader.stream_position()?;
         let bxon = Bxon::new(&mut reader)?;
 
         reader.seek(std::io::SeekFrom::Start(bxon.offset_asset_data))?;
@@ -70,7 +80,9 @@ impl BxonManager {
                 Box::new(asset)
             },
             _ => {
-                let asset = UnknownBxonAssetManager::new(path.clone(), runtime.clone(), bxon.asset_type.clone())?;
+                reader.seek(std::io::SeekFrom::Start(start))?;
+                let data = read_block(&mut reader, size)?;
+                let asset = UnknownBxonAssetManager::new(path.clone(), runtime.clone(), bxon.asset_type.clone(), data)?;
                 Box::new(asset)
             }
         };
@@ -109,6 +121,21 @@ impl Manager for BxonManager {
     fn paint_floating(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         self.contents.paint_floating(ui, toasts);
     }
+
+    fn take_pending_opens(&mut self) -> Vec<PendingOpen> {
+        self.contents.take_pending_opens()
+    }
+
+    // The BXON header wraps the asset's own document, its tables are passed through
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        let contents = self.contents.data_export()?;
+        let mut data_export = DataExport::new(&serde_json::json!({
+            "bxon": &self.bxon,
+            "asset": contents.as_ref().map(|contents| &contents.document),
+        }))?;
+        data_export.tables = contents.map(|contents| contents.tables).unwrap_or_default();
+        Ok(Some(data_export))
+    }
 }
 
 impl Resource for BxonManager {
@@ -119,24 +146,43 @@ impl Resource for BxonManager {
     fn set_resource(&mut self, resource: Vec<u8>) {
         self.contents.set_resource(resource);
     }
+
+    fn as_texture(&self) -> Option<&TpGxTexHeadManager> {
+        self.contents.as_texture()
+    }
 }
 
 impl ResourceManager for BxonManager {}
 
+// Reads up to `size` bytes, or everything left when there is no size
+pub(crate) fn read_block<R: Read>(mut reader: R, size: Option<u64>) -> Result<Vec<u8>, std::io::Error> {
+    let mut data = Vec::new();
+    match size {
+        Some(size) => reader.take(size).read_to_end(&mut data)?,
+        None => reader.read_to_end(&mut data)?,
+    };
+    Ok(data)
+}
+
 struct UnknownBxonAssetManager {
     path: PathBuf,
     runtime: tokio::runtime::Handle,
 
     asset_type: String,
+    // The whole BXON block, header included, so offsets in the viewer match the file
+    data: Vec<u8>,
+    hex_viewer: HexViewer,
 }
 
 impl UnknownBxonAssetManager {
-    pub fn new(path: PathBuf, runtime: tokio::runtime::Handle, asset_type: String) -> Result<Self, std::io::Error> {
+    pub fn new(path: PathBuf, runtime: tokio::runtime::Handle, asset_type: String, data: Vec<u8>) -> Result<Self, std::io::Error> {
         Ok(Self {
             path,
             runtime,
 
-            asset_type
+            asset_type,
+            data,
+            hex_viewer: HexViewer::new("unknown_bxon_asset"),
         })
     }
 }
@@ -150,6 +196,7 @@ impl Manager for UnknownBxonAssetManager {
         egui::Frame::window(&ui.style()).show(ui, |ui| {
             ui.label(format!("Unknown BXON asset type: {}", self.asset_type));
         });
+        self.hex_viewer.show(ui, &self.data);
     }
 }
 
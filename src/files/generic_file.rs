@@ -3,14 +3,27 @@ use std::path::PathBuf;
 use eframe::egui;
 
 use crate::traits::*;
+use crate::widgets::hex_viewer::HexViewer;
+
+// Unknown files can be whole archives or movies, only this much is read for the hex view
+pub const PREVIEW_SIZE: u64 = 4 * 1024 * 1024;
 
 pub struct GenericFileManager {
     pub path: PathBuf,
+    size: u64,
+    // The first PREVIEW_SIZE bytes at most
+    data: Vec<u8>,
+    hex_viewer: HexViewer,
 }
 
 impl GenericFileManager {
-    pub fn new(path: PathBuf) -> Self {
-        Self { path }
+    pub fn new(path: PathBuf, size: u64, data: Vec<u8>) -> Self {
+        Self {
+            path,
+            size,
+            data,
+            hex_viewer: HexViewer::new("generic_file"),
+        }
     }
 }
 
@@ -20,10 +33,15 @@ impl Manager for GenericFileManager {
     }
 
     fn paint(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
-        ui.with_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown), |ui| {
-            ui.style_mut().interaction.selectable_labels = false;
-            ui.label(egui::RichText::new(egui_phosphor::regular::SEAL_QUESTION).size(128.0));
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(egui_phosphor::regular::SEAL_QUESTION).size(24.0));
+            ui.label(format!("Unrecognised file, {} bytes", self.size));
         });
+        if (self.data.len() as u64) < self.size {
+            ui.label(format!("Showing and searching the first {} only.", crate::util::format_size(self.data.len() as u64)));
+        }
+        ui.separator();
+        self.hex_viewer.show(ui, &self.data);
     }
 
     fn title(&self) -> String {
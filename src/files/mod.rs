@@ -1,6 +1,8 @@
+use std::io::{Read, Seek};
 use std::path::PathBuf;
 
 use crate::traits::*;
+use crate::widgets::hex_viewer::HexViewer;
 
 pub mod generic_file;
 pub mod bxon;
@@ -9,21 +11,43 @@ pub mod zstd;
 pub mod pack;
 pub mod tp_gx_tex_head;
 
+// Picks a manager by the file's magic, anything unrecognised opens as a generic file
+pub fn open<R: Read + Seek>(path: PathBuf, runtime: tokio::runtime::Handle, mut reader: R) -> Result<Box<dyn Manager>, std::io::Error> {
+    let mut file_magic = [0; 4];
+    reader.read_exact(&mut file_magic)?;
+    reader.seek(std::io::SeekFrom::Start(0))?;
+
+    Ok(match &file_magic {
+        [0x28, 0xB5, 0x2F, 0xFD] => Box::new(zstd::ZstdManager::new(path, runtime, reader)?),
+        b"PACK" => Box::new(pack::PackManager::new(path, runtime, reader)?),
+        b"BXON" => Box::new(bxon::BxonManager::new(path, runtime, reader)?),
+        _ => {
+            let size = reader.seek(std::io::SeekFrom::End(0))?;
+            reader.seek(std::io::SeekFrom::Start(0))?;
+            Box::new(generic_file::GenericFileManager::new(path, size, bxon::read_block(reader, Some(generic_file::PREVIEW_SIZE))?))
+        },
+    })
+}
+
 struct UnknownFile {}
 
 pub struct UnknownFileManager {
     path: PathBuf,
     runtime: tokio::runtime::Handle,
 
-    unknown_file: UnknownFile
+    unknown_file: UnknownFile,
+    data: Vec<u8>,
+    hex_viewer: HexViewer,
 }
 
 impl UnknownFileManager {
-    pub fn new(path: PathBuf, runtime: tokio::runtime::Handle) -> Result<Self, std::io::Error> {
+    pub fn new(path: PathBuf, runtime: tokio::runtime::Handle, data: Vec<u8>) -> Result<Self, std::io::Error> {
         Ok(Self {
             path,
             runtime,
-            unknown_file: UnknownFile {}
+            unknown_file: UnknownFile {},
+            data,
+            hex_viewer: HexViewer::new("unknown_file"),
         })
     }
 }
@@ -35,6 +59,7 @@ impl Manager for UnknownFileManager {
 
     fn paint(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         ui.label("Unknown Replicant file");
+        self.hex_viewer.show(ui, &self.data);
     }
 
     fn title(&self) -> String {
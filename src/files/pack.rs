@@ -1,12 +1,17 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Seek;
 use std::path::PathBuf;
+use std::sync::Arc;
 use byteorder::ReadBytesExt;
 use eframe::egui;
 
-use crate::{traits::*, util::ReadUtilExt};
+use crate::{data_export::{self, DataExport}, dictionary, extract, filter::FilterEntry, hash, jobs::Jobs, texture::batch, traits::*, util::ReadUtilExt, widgets::{batch_export_dialog::BatchExportDialog, filter_edit::FilterEdit, table::SortableTable, virtual_list::VirtualList}};
 
-use super::{bxon::BxonManager, UnknownFileManager};
+use super::{bxon::{read_block, BxonManager}, tp_archive_file_param::paint_hash_warning, tp_gx_tex_head::TpGxTexHeadManager, UnknownFileManager};
 
+#[derive(serde::Serialize)]
 struct Pack {
+    #[serde(serialize_with = "crate::data_export::serialize_magic")]
     id: [u8; 4],
     version: u32,
     total_size: u32,
@@ -67,19 +72,85 @@ impl Pack {
     }
 }
 
+const THUMBNAIL_SIZE: f32 = 96.0;
+// Thumbnails are decoded on the UI thread, so only a few are generated per frame
+const THUMBNAILS_PER_FRAME: usize = 4;
+
+const SPLIT_MANIFEST_FILE_NAME: &str = "pack_manifest.json";
+
+#[derive(Clone, Copy)]
+enum RawEntry {
+    Asset(usize),
+    File(usize),
+}
+
+// Where an entry's serialized block and resource sit in the PACK
+struct RawEntryRanges {
+    name: String,
+    hash: u32,
+    range: std::ops::Range<usize>,
+    resource_range: Option<std::ops::Range<usize>>,
+}
+
+#[derive(serde::Serialize)]
+struct RawEntryRecord {
+    kind: &'static str,
+    index: usize,
+    name: String,
+    hash: u32,
+    offset: u64,
+    size: u64,
+    path: String,
+    resource_offset: Option<u64>,
+    resource_size: Option<u64>,
+    resource_path: Option<String>,
+}
+
+fn write_raw_file(path: &std::path::Path, data: &[u8]) -> Result<(), std::io::Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, data)
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum FilesView {
+    List,
+    Gallery,
+}
+
 pub struct PackManager {
     path: PathBuf,
     runtime: tokio::runtime::Handle,
 
+    data: Arc<Vec<u8>>,
     pack: Pack,
     imports: Vec<Import>,
     assets: Vec<AssetManager>,
     files: Vec<FileManager>,
-    files_filter: String,
+    imports_table: SortableTable,
+    // Import indices in table order and the dictionary size they were sorted with
+    imports_order: Option<(usize, Vec<usize>)>,
+    assets_list: VirtualList,
+    files_list: VirtualList,
+    files_filter: FilterEdit,
+    lookup: String,
+    lookup_results: Vec<String>,
+    files_view: FilesView,
+    thumbnails: HashMap<usize, Option<egui::TextureHandle>>,
+    open_textures: Vec<usize>,
+
+    jobs: Jobs,
+    batch_export_dialog: BatchExportDialog,
 }
 
 impl PackManager {
     pub fn new<R: std::io::Read + std::io::Seek>(path: PathBuf, runtime: tokio::runtime::Handle, mut reader: R) -> Result<Self, std::io::Error> {
+        let mut data = Vec::new();
+        reader.seek(std::io::SeekFrom::Start(0))?;
+        reader.read_to_end(&mut data)?;
+        let mut reader = std::io::Cursor::new(&data);
+
         let pack = Pack::new(&mut reader)?;
 
         reader.seek(std::io::SeekFrom::Start(pack.offset_imports))?;
@@ -100,11 +171,23 @@ impl PackManager {
             files.push(FileManager::new(path.clone(), runtime.clone(),&mut reader)?);
         }
 
+        for import in imports.iter() {
+            dictionary::observe(&import.path, import.hash);
+        }
+        for asset_manager in assets.iter() {
+            dictionary::observe(&asset_manager.asset.name, asset_manager.asset.hash);
+        }
+        for file_manager in files.iter() {
+            dictionary::observe(&file_manager.file.name, file_manager.file.hash);
+        }
+
         reader.seek(std::io::SeekFrom::Start(pack.serialized_size as u64))?;
         for file in files.iter_mut() {
             let resource_size = file.contents.get_resource_size();
             if resource_size > 0 {
+                let resource_offset = reader.stream_position()? as usize;
                 let resource = Resource::new(&mut reader, resource_size as usize, pack.serialized_size as u64)?;
+                file.resource_range = Some(resource_offset..resource_offset + resource_size as usize);
                 file.set_resource(resource.data);
             }
         }
@@ -113,14 +196,289 @@ impl PackManager {
             path,
             runtime,
 
+            data: Arc::new(data),
             pack,
             imports,
             assets,
             files,
 
-            files_filter: String::new()
+            imports_table: SortableTable::new("pack_imports", &["Path", "Hash"]),
+            imports_order: None,
+            assets_list: VirtualList::new("pack_assets", 40.0),
+            files_list: VirtualList::new("pack_files", 40.0),
+            files_filter: FilterEdit::default(),
+            lookup: String::new(),
+            lookup_results: Vec::new(),
+            files_view: FilesView::List,
+            thumbnails: HashMap::new(),
+            open_textures: Vec::new(),
+
+            jobs: Jobs::default(),
+            batch_export_dialog: BatchExportDialog::default(),
+        })
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = (String, &TpGxTexHeadManager)> {
+        self.files.iter().filter_map(|file_manager| file_manager.contents.as_texture().map(|texture| (file_manager.file.display_name(), texture)))
+    }
+
+    fn export_textures(&mut self, options: batch::BatchExportOptions) {
+        let Some(output_folder) = rfd::FileDialog::new().set_title("Export textures").pick_folder() else {
+            return;
+        };
+
+        let job = self.jobs.start(format!("Exporting textures from {}", self.path.file_name().unwrap_or_default().to_str().unwrap_or_default()), 1);
+        let path = self.path.clone();
+        let data = self.data.clone();
+        let runtime = self.runtime.clone();
+        self.runtime.spawn_blocking(move || {
+            batch::export_pack_textures_job(path, runtime, &data, &output_folder, options, &job);
+        });
+    }
+
+    fn raw_entry(&self, entry: RawEntry) -> Result<RawEntryRanges, std::io::Error> {
+        let (name, hash, range, resource_range) = match entry {
+            RawEntry::Asset(index) => {
+                let asset = &self.assets[index].asset;
+                (asset.display_name(), asset.hash, asset.offset_data_start as usize..asset.offset_data_end as usize, None)
+            },
+            RawEntry::File(index) => {
+                let file_manager = &self.files[index];
+                let start = file_manager.file.offset_data_start as usize;
+                (file_manager.file.display_name(), file_manager.file.hash, start..start + file_manager.file.size as usize, file_manager.resource_range.clone())
+            },
+        };
+        let in_bounds = |range: &std::ops::Range<usize>| range.start <= range.end && range.end <= self.data.len();
+        if !in_bounds(&range) || !resource_range.as_ref().is_none_or(in_bounds) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{} points outside the PACK.", name)));
+        }
+        Ok(RawEntryRanges { name, hash, range, resource_range })
+    }
+
+    // Writes the serialized block as "<name>.bxon" (".bin" when it is not BXON) and the resource blob as "<name>.resource",
+    // both under `relative_folder` in the output folder. Names already in `used` (compared case-insensitively) get the hash appended.
+    fn write_raw_entry(&self, entry: RawEntry, output_folder: &std::path::Path, relative_folder: &str, used: &mut HashSet<String>) -> Result<RawEntryRecord, std::io::Error> {
+        let RawEntryRanges { name, hash, range, resource_range } = self.raw_entry(entry)?;
+        let block = &self.data[range.clone()];
+        let base_path = PathBuf::from(relative_folder).join(extract::sanitize_path(&name).unwrap_or_else(|_| PathBuf::from(format!("{:08X}", hash))));
+        let mut relative_path = base_path.clone();
+        let mut attempt = 0;
+        while !used.insert(relative_path.to_str().unwrap_or_default().to_lowercase()) {
+            attempt += 1;
+            let mut suffixed = base_path.clone().into_os_string();
+            match attempt {
+                1 => suffixed.push(format!("_{:08X}", hash)),
+                _ => suffixed.push(format!("_{:08X}_{}", hash, attempt)),
+            }
+            relative_path = PathBuf::from(suffixed);
+        }
+        let extension = match block.starts_with(b"BXON") {
+            true => "bxon",
+            false => "bin",
+        };
+
+        let mut block_path = relative_path.clone().into_os_string();
+        block_path.push(format!(".{}", extension));
+        let block_path = PathBuf::from(block_path);
+        write_raw_file(&output_folder.join(&block_path), block)?;
+
+        let resource_path = match &resource_range {
+            Some(resource_range) => {
+                let mut resource_path = relative_path.into_os_string();
+                resource_path.push(".resource");
+                let resource_path = PathBuf::from(resource_path);
+                write_raw_file(&output_folder.join(&resource_path), &self.data[resource_range.clone()])?;
+                Some(resource_path)
+            },
+            None => None,
+        };
+
+        let (kind, index) = match entry {
+            RawEntry::Asset(index) => ("asset", index),
+            RawEntry::File(index) => ("file", index),
+        };
+        Ok(RawEntryRecord {
+            kind,
+            index,
+            name,
+            hash,
+            offset: range.start as u64,
+            size: range.len() as u64,
+            path: block_path.to_str().unwrap_or_default().replace('\\', "/"),
+            resource_offset: resource_range.as_ref().map(|resource_range| resource_range.start as u64),
+            resource_size: resource_range.as_ref().map(|resource_range| resource_range.len() as u64),
+            resource_path: resource_path.map(|resource_path| resource_path.to_str().unwrap_or_default().replace('\\', "/")),
         })
     }
+
+    fn export_raw(&self, entry: RawEntry, toasts: &mut egui_notify::Toasts) {
+        let Some(output_folder) = rfd::FileDialog::new().set_title("Export raw").pick_folder() else {
+            return;
+        };
+        match self.write_raw_entry(entry, &output_folder, "", &mut HashSet::new()) {
+            Ok(record) => {
+                toasts.success(format!("Exported {}.", record.path)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to export raw data: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    // Every asset and file as standalone files, plus a manifest with the header and imports to put them back together.
+    // Assets and files go to separate folders since their names may overlap.
+    pub fn split(&self, output_folder: &std::path::Path) -> Result<usize, std::io::Error> {
+        let mut used = HashSet::new();
+        let entries = (0..self.assets.len()).map(|index| (RawEntry::Asset(index), "assets")).chain((0..self.files.len()).map(|index| (RawEntry::File(index), "files")));
+        let records = entries.map(|(entry, relative_folder)| self.write_raw_entry(entry, output_folder, relative_folder, &mut used)).collect::<Result<Vec<_>, _>>()?;
+
+        let manifest = serde_json::json!({
+            "source": self.path.to_str().unwrap_or_default(),
+            "header": &self.pack,
+            "imports": &self.imports,
+            "entries": &records,
+        });
+        let json = serde_json::to_string_pretty(&manifest).map_err(std::io::Error::other)?;
+        std::fs::write(output_folder.join(SPLIT_MANIFEST_FILE_NAME), json)?;
+        Ok(records.len())
+    }
+
+    fn split_dialog(&self, toasts: &mut egui_notify::Toasts) {
+        let Some(output_folder) = rfd::FileDialog::new().set_title("Split pack").pick_folder() else {
+            return;
+        };
+        match self.split(&output_folder) {
+            Ok(count) => {
+                toasts.success(format!("Wrote {} entries.", count)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to split pack: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    fn find(&mut self) {
+        let query = &self.lookup;
+        let imports = self.imports.iter().enumerate().filter(|(_, import)| hash::matches_query(query, &import.path, import.hash)).map(|(index, import)| format!("Import #{}: {}", index, import.path));
+        let assets = self.assets.iter().enumerate().filter(|(_, asset_manager)| hash::matches_query(query, &asset_manager.asset.name, asset_manager.asset.hash)).map(|(index, asset_manager)| format!("Asset #{}: {}", index, asset_manager.asset.display_name()));
+        let files = self.files.iter().enumerate().filter(|(_, file_manager)| hash::matches_query(query, &file_manager.file.name, file_manager.file.hash)).map(|(index, file_manager)| format!("File #{}: {}", index, file_manager.file.display_name()));
+        self.lookup_results = imports.chain(assets).chain(files).collect();
+        if let Some(hash) = hash::parse_hash(query) && let Some(name) = dictionary::resolve(hash) {
+            self.lookup_results.push(format!("Dictionary: {:08X} is {}", hash, name));
+        }
+        if self.lookup_results.is_empty() {
+            self.lookup_results.push(format!("Nothing matches \"{}\".", query.trim()));
+        }
+    }
+
+    fn paint_gallery(&mut self, ui: &mut eframe::egui::Ui) {
+        let indices = self.files.iter()
+            .enumerate()
+            .filter(|(_, file_manager)| file_manager.contents.as_texture().is_some())
+            .filter(|(_, file_manager)| file_manager.matches(&self.files_filter))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if indices.is_empty() {
+            ui.label("No textures found.");
+            return;
+        }
+
+        let tile_size = egui::vec2(THUMBNAIL_SIZE + 8.0, THUMBNAIL_SIZE + 24.0);
+        let columns = ((ui.available_width() + ui.spacing().item_spacing.x) / (tile_size.x + ui.spacing().item_spacing.x)).floor().max(1.0) as usize;
+        let rows = indices.len().div_ceil(columns);
+
+        let files = &self.files;
+        let thumbnails = &mut self.thumbnails;
+        let open_textures = &mut self.open_textures;
+        let mut generated = 0;
+        egui::ScrollArea::vertical()
+            .id_salt("pack_gallery")
+            .show_rows(ui, tile_size.y, rows, |ui, row_range| {
+                for row in row_range {
+                    ui.horizontal(|ui| {
+                        for &index in indices.iter().skip(row * columns).take(columns) {
+                            let file_manager = &files[index];
+                            let Some(texture) = file_manager.contents.as_texture() else {
+                                continue;
+                            };
+
+                            if !thumbnails.contains_key(&index) && generated < THUMBNAILS_PER_FRAME {
+                                generated += 1;
+                                let thumbnail = match texture.thumbnail(THUMBNAIL_SIZE as u32) {
+                                    Ok(image) => {
+                                        let color_image = egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw());
+                                        Some(ui.ctx().load_texture(format!("{}#{}", file_manager.file.display_name(), index), color_image, egui::TextureOptions::LINEAR))
+                                    },
+                                    Err(e) => {
+                                        println!("Failed to generate thumbnail for {}: {}", file_manager.file.display_name(), e);
+                                        None
+                                    }
+                                };
+                                thumbnails.insert(index, thumbnail);
+                            }
+
+                            let (rect, response) = ui.allocate_exact_size(tile_size, egui::Sense::click());
+                            let visuals = ui.style().interact(&response);
+                            ui.painter().rect_filled(rect, visuals.corner_radius, visuals.bg_fill);
+
+                            let image_rect = egui::Rect::from_min_size(rect.min + egui::vec2(4.0, 4.0), egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE));
+                            match thumbnails.get(&index) {
+                                Some(Some(handle)) => {
+                                    let size = handle.size_vec2();
+                                    let scale = THUMBNAIL_SIZE / size.x.max(size.y);
+                                    let fitted_rect = egui::Rect::from_center_size(image_rect.center(), size * scale);
+                                    ui.painter().image(handle.id(), fitted_rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
+                                },
+                                Some(None) => {
+                                    ui.painter().text(image_rect.center(), egui::Align2::CENTER_CENTER, egui_phosphor::regular::WARNING, egui::FontId::proportional(32.0), visuals.text_color());
+                                },
+                                None => {
+                                    ui.painter().text(image_rect.center(), egui::Align2::CENTER_CENTER, egui_phosphor::regular::HOURGLASS, egui::FontId::proportional(32.0), visuals.text_color());
+                                }
+                            }
+
+                            let name_rect = egui::Rect::from_min_max(egui::pos2(rect.min.x + 4.0, image_rect.max.y), rect.max - egui::vec2(4.0, 0.0));
+                            ui.painter().with_clip_rect(name_rect).text(name_rect.left_center(), egui::Align2::LEFT_CENTER, file_manager.file.display_name(), egui::FontId::proportional(12.0), visuals.text_color());
+
+                            let response = response.on_hover_text(format!("{}\n{}×{}\n{}", file_manager.file.display_name(), texture.width(), texture.height(), texture.format_name()));
+                            if response.clicked() && !open_textures.contains(&index) {
+                                open_textures.push(index);
+                            }
+                        }
+                    });
+                }
+            });
+
+        if generated > 0 {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn paint_texture_windows(&mut self, ctx: &egui::Context, toasts: &mut egui_notify::Toasts) {
+        let mut closed = Vec::new();
+        for &index in self.open_textures.iter() {
+            let file_manager = &mut self.files[index];
+            let mut open = true;
+            egui::Window::new(format!("{} {}", egui_phosphor::regular::IMAGE, file_manager.file.display_name()))
+                .id(egui::Id::new(&self.path).with("texture_window").with(index))
+                .open(&mut open)
+                .default_width(560.0)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        file_manager.contents.paint(ui, toasts);
+                    });
+                });
+            if !open {
+                closed.push(index);
+            }
+        }
+
+        // The texture may have been re-imported while open, so its thumbnail is regenerated
+        for index in closed {
+            self.open_textures.retain(|open_index| *open_index != index);
+            self.thumbnails.remove(&index);
+        }
+    }
 }
 
 impl Manager for PackManager {
@@ -128,44 +486,95 @@ impl Manager for PackManager {
         &self.path
     }
 
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        let assets = self.assets.iter().map(|asset_manager| &asset_manager.asset).collect::<Vec<_>>();
+        let files = self.files.iter().map(|file_manager| &file_manager.file).collect::<Vec<_>>();
+
+        // Texture headers and their surfaces, each tagged with the file it belongs to
+        let mut textures = Vec::new();
+        let mut surfaces = Vec::new();
+        for (name, texture) in self.textures() {
+            let Some(texture_export) = texture.data_export()? else {
+                continue;
+            };
+            textures.push(data_export::with_field("file", &name, texture_export.document));
+            for table in texture_export.tables.into_iter().filter(|table| table.name == "surfaces") {
+                surfaces.extend(table.rows.into_iter().map(|row| data_export::with_field("texture", &name, row)));
+            }
+        }
+
+        let data_export = DataExport::new(&serde_json::json!({
+            "header": &self.pack,
+            "imports": &self.imports,
+            "assets": &assets,
+            "files": &files,
+            "textures": &textures,
+        }))?;
+        Ok(Some(data_export.with_table("imports", self.imports.iter())?.with_table("assets", assets.iter())?.with_table("files", files.iter())?
+            .with_table("textures", textures.iter())?.with_table("surfaces", surfaces.iter())?))
+    }
+
     fn paint(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         ui.label(format!("Version: {}", self.pack.version));
         ui.label(format!("Total Size: {}", self.pack.total_size));
         ui.label(format!("Serialized Size: {}", self.pack.serialized_size));
         ui.label(format!("Resource Size: {}", self.pack.resources_size));
 
+        ui.horizontal(|ui| {
+            ui.label("Find path or hash:");
+            let response = ui.text_edit_singleline(&mut self.lookup);
+            if (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) || ui.button(egui_phosphor::regular::MAGNIFYING_GLASS).clicked() {
+                self.find();
+            }
+        });
+        for result in self.lookup_results.iter() {
+            ui.label(result);
+        }
+
         ui.separator();
 
         ui.collapsing(egui::RichText::new(format!("{} Imports", egui_phosphor::regular::ARROW_SQUARE_IN)).heading(), |ui| {
             if self.imports.is_empty() {
                 ui.label("No imports found.");
             } else {
-                egui_extras::TableBuilder::new(ui)
-                .id_salt("pack_imports")
-                .striped(true)
-                .resizable(true)
-                .columns(egui_extras::Column::auto(), 2)
-                .header(16.0, |mut header| {
-                    header.col(|ui| {
-                        ui.heading("Path");
+                // Names are only looked up again when the sort keys or the dictionary change
+                if self.imports_order.as_ref().is_none_or(|(dictionary_len, _)| *dictionary_len != dictionary::len()) {
+                    let names = self.imports.iter().map(|import| dictionary::display_name(&import.path, import.hash)).collect::<Vec<_>>();
+                    let mut order = (0..self.imports.len()).collect::<Vec<_>>();
+                    self.imports_table.sort(&mut order, |a, b, column| match column {
+                        0 => names[*a].cmp(&names[*b]),
+                        _ => self.imports[*a].hash.cmp(&self.imports[*b].hash),
                     });
-                    header.col(|ui| {
-                        ui.heading("Hash");
-                    });
-                })
-                .body(|mut body| {
-                    for import in self.imports.iter() {
-                        body.row(16.0, |mut row| {
-                            row.col(|ui| {
-                                ui.add(egui::Label::new(format!("{}", import.path)).extend());
-                            });
-                            row.col(|ui| {
+                    self.imports_order = Some((dictionary::len(), order));
+                }
+                let imports = self.imports_order.as_ref().map(|(_, order)| order.iter().map(|index| &self.imports[*index]).collect::<Vec<_>>()).unwrap_or_default();
+                let response = self.imports_table.show(
+                    ui,
+                    imports.len(),
+                    None,
+                    |_| false,
+                    |index, column| match column {
+                        0 => dictionary::display_name(&imports[index].path, imports[index].hash),
+                        _ => format!("{:08X}", imports[index].hash),
+                    },
+                    |ui, index, column| {
+                        let import = imports[index];
+                        match column {
+                            0 => {
+                                ui.add(egui::Label::new(dictionary::display_name(&import.path, import.hash)).extend());
+                            },
+                            _ => {
                                 ui.style_mut().override_font_id = Some(egui::FontId::monospace(12.0));
                                 ui.add(egui::Label::new(format!("{:08X}", import.hash)).extend());
-                            });
-                        });
-                    }
-                });
+                                paint_hash_warning(ui, &import.path, import.hash);
+                            },
+                        }
+                    },
+                );
+                if response.sort_changed {
+                    self.imports_order = None;
+                    ui.ctx().request_repaint();
+                }
             }
         });
 
@@ -175,12 +584,24 @@ impl Manager for PackManager {
             if self.assets.is_empty() {
                 ui.label("No assets found.");
             } else {
-                for asset_manager in self.assets.iter_mut() {
+                let entries = (0..self.assets.len()).collect::<Vec<_>>();
+                let assets = &mut self.assets;
+                let mut export_request = None;
+                self.assets_list.show(ui, &entries, |ui, index| {
+                    let asset_manager = &mut assets[index];
                     egui::Frame::window(&ui.style()).show(ui, |ui| {
-                        ui.collapsing(egui::RichText::new(format!("{} ({})", asset_manager.asset.name, asset_manager.contents.title())).heading(), |ui| {
-                            asset_manager.contents.paint(ui, toasts);
-                        });
+                        egui::CollapsingHeader::new(egui::RichText::new(format!("{} ({}){}", asset_manager.asset.display_name(), asset_manager.contents.title(), hash_warning(&asset_manager.asset.name, asset_manager.asset.hash))).heading())
+                            .id_salt(("pack_asset", index))
+                            .show(ui, |ui| {
+                                if ui.button("Export raw…").on_hover_text("Write the serialized BXON block").clicked() {
+                                    export_request = Some(RawEntry::Asset(index));
+                                }
+                                asset_manager.contents.paint(ui, toasts);
+                            });
                     });
+                });
+                if let Some(entry) = export_request {
+                    self.export_raw(entry, toasts);
                 }
             }
         });
@@ -193,18 +614,34 @@ impl Manager for PackManager {
             } else {
                 ui.horizontal(|ui| {
                     ui.label("Filter: ");
-                    ui.text_edit_singleline(&mut self.files_filter);
+                    self.files_filter.show(ui);
+                    ui.separator();
+                    ui.selectable_value(&mut self.files_view, FilesView::List, format!("{} List", egui_phosphor::regular::LIST));
+                    ui.selectable_value(&mut self.files_view, FilesView::Gallery, format!("{} Gallery", egui_phosphor::regular::SQUARES_FOUR));
                 });
-                egui::ScrollArea::vertical()
-                .show(ui, |ui| {
-                    for file_manager in self.files.iter_mut().filter(|file_manager| self.files_filter.is_empty() || file_manager.file.name.contains(&self.files_filter)) {
-                        egui::Frame::window(&ui.style()).show(ui, |ui| {
-                            ui.collapsing(egui::RichText::new(format!("{} ({})", file_manager.file.name, file_manager.contents.title())).heading(), |ui| {
+                if self.files_view == FilesView::Gallery {
+                    self.paint_gallery(ui);
+                    return;
+                }
+                let entries = self.files.iter().enumerate().filter(|(_, file_manager)| file_manager.matches(&self.files_filter)).map(|(index, _)| index).collect::<Vec<_>>();
+                let files = &mut self.files;
+                let mut export_request = None;
+                self.files_list.show(ui, &entries, |ui, index| {
+                    let file_manager = &mut files[index];
+                    egui::Frame::window(&ui.style()).show(ui, |ui| {
+                        egui::CollapsingHeader::new(egui::RichText::new(format!("{} ({}){}", file_manager.file.display_name(), file_manager.contents.title(), hash_warning(&file_manager.file.name, file_manager.file.hash))).heading())
+                            .id_salt(("pack_file", index))
+                            .show(ui, |ui| {
+                                if ui.button("Export raw…").on_hover_text("Write the serialized BXON block and its resource").clicked() {
+                                    export_request = Some(RawEntry::File(index));
+                                }
                                 file_manager.contents.paint(ui, toasts);
                             });
-                        });
-                    }
+                    });
                 });
+                if let Some(entry) = export_request {
+                    self.export_raw(entry, toasts);
+                }
             }
         });
     }
@@ -212,8 +649,43 @@ impl Manager for PackManager {
     fn title(&self) -> String {
         format!("{} PACK", egui_phosphor::regular::PACKAGE)
     }
+
+    fn paint_top_bar(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
+        ui.menu_button(format!("{} Textures", egui_phosphor::regular::IMAGES), |ui| {
+            if ui.button("Export all…").clicked() {
+                self.batch_export_dialog.open();
+                ui.close_menu();
+            }
+        });
+
+        ui.menu_button(format!("{} Raw", egui_phosphor::regular::BINARY), |ui| {
+            if ui.button("Split pack…").on_hover_text("Write every asset and file as standalone files with a manifest").clicked() {
+                self.split_dialog(toasts);
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn paint_floating(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
+        let source = self.path.to_str().unwrap_or_default().to_string();
+        if let Some(options) = self.batch_export_dialog.show(ui.ctx(), egui::Id::new(&self.path).with("batch_export"), &source) {
+            self.export_textures(options);
+        }
+        self.paint_texture_windows(ui.ctx(), toasts);
+        self.jobs.paint(ui.ctx());
+    }
 }
 
+fn hash_warning(path: &str, hash: u32) -> String {
+    match (hash::verify(path, hash), dictionary::resolve(hash)) {
+        (true, _) => String::new(),
+        (false, Some(name)) => format!(" {} hash {:08X} belongs to {}", egui_phosphor::regular::WARNING, hash, name),
+        (false, None) => format!(" {} hash {:08X} does not match", egui_phosphor::regular::WARNING, hash),
+    }
+}
+
+
+#[derive(serde::Serialize)]
 struct Import {
     hash: u32,
     relative_offset: u32,
@@ -243,6 +715,7 @@ impl Import {
     }
 }
 
+#[derive(serde::Serialize)]
 struct Asset {
     hash: u32,
     relative_offset_name: u32,
@@ -257,6 +730,10 @@ struct Asset {
 }
 
 impl Asset {
+    fn display_name(&self) -> String {
+        dictionary::display_name(&self.name, self.hash)
+    }
+
     pub fn new<R: std::io::Read + std::io::Seek>(mut reader: R) -> Result<Self, std::io::Error> {
         let hash = reader.read_u32::<byteorder::LittleEndian>()?;
         let (offset_name, relative_offset_name) = reader.read_offsets::<byteorder::LittleEndian>()?;
@@ -300,12 +777,13 @@ impl AssetManager {
         let mut content_magic = [0; 4];
         reader.read_exact(&mut content_magic)?;
         reader.seek(std::io::SeekFrom::Start(asset.offset_data_start))?;
+        let size = Some(asset.offset_data_end.saturating_sub(asset.offset_data_start));
         let contents: Box<dyn Manager> = match &content_magic {
             b"BXON" => {
-                Box::new(BxonManager::new(asset.name.clone().into(), runtime.clone(), &mut reader)?)
+                Box::new(BxonManager::with_size(asset.name.clone().into(), runtime.clone(), &mut reader, size)?)
             },
             _ => {
-                Box::new(UnknownFileManager::new(path.clone(), runtime.clone())?)
+                Box::new(UnknownFileManager::new(path.clone(), runtime.clone(), read_block(&mut reader, size)?)?)
             }
         };
         reader.seek(std::io::SeekFrom::Start(return_pos))?;
@@ -320,6 +798,7 @@ impl AssetManager {
     }
 }
 
+#[derive(serde::Serialize)]
 struct File {
     hash: u32,
     relative_offset_name: u32,
@@ -332,6 +811,10 @@ struct File {
 }
 
 impl File {
+    fn display_name(&self) -> String {
+        dictionary::display_name(&self.name, self.hash)
+    }
+
     pub fn new<R: std::io::Read + std::io::Seek>(mut reader: R) -> Result<Self, std::io::Error> {
         let hash = reader.read_u32::<byteorder::LittleEndian>()?;
         let (offset_name, relative_offset_name) = reader.read_offsets::<byteorder::LittleEndian>()?;
@@ -362,7 +845,9 @@ struct FileManager {
     runtime: tokio::runtime::Handle,
 
     file: File,
-    contents: Box<dyn ResourceManager>
+    contents: Box<dyn ResourceManager>,
+    // Where the resource blob sits in the PACK, if the file has one
+    resource_range: Option<std::ops::Range<usize>>,
 }
 
 impl FileManager {
@@ -374,12 +859,13 @@ impl FileManager {
         let mut content_magic = [0; 4];
         reader.read_exact(&mut content_magic)?;
         reader.seek(std::io::SeekFrom::Start(file.offset_data_start))?;
+        let size = Some(file.size as u64);
         let contents: Box<dyn ResourceManager> = match &content_magic {
             b"BXON" => {
-                Box::new(BxonManager::new(file.name.clone().into(), runtime.clone(), &mut reader)?)
+                Box::new(BxonManager::with_size(file.name.clone().into(), runtime.clone(), &mut reader, size)?)
             },
             _ => {
-                Box::new(UnknownFileManager::new(path.clone(), runtime.clone())?)
+                Box::new(UnknownFileManager::new(path.clone(), runtime.clone(), read_block(&mut reader, size)?)?)
             }
         };
         reader.seek(std::io::SeekFrom::Start(return_pos))?;
@@ -389,7 +875,8 @@ impl FileManager {
             runtime,
 
             file,
-            contents
+            contents,
+            resource_range: None,
         })
     }
 
@@ -400,6 +887,16 @@ impl FileManager {
     fn get_resource_size(&self) -> u32 {
         self.contents.get_resource_size()
     }
+
+    fn matches(&self, filter: &FilterEdit) -> bool {
+        let name = self.file.display_name();
+        filter.is_empty() || filter.filter().matches(&FilterEntry {
+            path: &name,
+            hash: self.file.hash,
+            size: Some(self.file.size as u64),
+            ..Default::default()
+        })
+    }
 }
 
 struct Resource {
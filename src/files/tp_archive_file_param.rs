@@ -1,21 +1,38 @@
-use std::collections::HashMap;
-use std::io::{Read, Seek, Write};
+use std::collections::{HashSet, VecDeque};
+use std::io::{Read, Seek};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use byteorder::ReadBytesExt;
 use eframe::egui;
 
+use crate::{dictionary, hash};
+use crate::data_export::DataExport;
+use crate::extract::{self, ExtractOptions, OverwritePolicy, WriteOutcome};
+use crate::filter::FilterEntry;
+use crate::index_document::IndexDocument;
+use crate::jobs::Jobs;
+use crate::manifest::{Manifest, ManifestEntry};
+use crate::texture::batch;
 use crate::traits::*;
 use crate::util::ReadUtilExt;
-
-struct TpArchiveFileParam {
-    archive_count: u32,
+use crate::vfs::MAX_CACHED_ARCHIVES;
+use crate::widgets::batch_export_dialog::BatchExportDialog;
+use crate::widgets::directory_tree::{self, DirectoryTree, DirectoryTreeAction};
+use crate::widgets::filter_edit::FilterEdit;
+use crate::widgets::table::SortableTable;
+
+// Opened archives, least recently used first
+type ArchivesCache = Arc<RwLock<VecDeque<(String, Arc<Archive>)>>>;
+
+#[derive(serde::Serialize)]
+pub(crate) struct TpArchiveFileParam {
+    pub(crate) archive_count: u32,
     rel_offset_archives: u32,
-    offset_archives: u64,
-    file_count: u32,
+    pub(crate) offset_archives: u64,
+    pub(crate) file_count: u32,
     rel_offset_files: u32,
-    offset_files: u64,
+    pub(crate) offset_files: u64,
 }
 
 impl TpArchiveFileParam {
@@ -36,6 +53,25 @@ impl TpArchiveFileParam {
     }
 }
 
+// Counts a file as opening for as long as it is alive
+struct OpeningGuard(Arc<std::sync::atomic::AtomicUsize>);
+
+impl OpeningGuard {
+    fn new(count: Arc<std::sync::atomic::AtomicUsize>) -> Self {
+        count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for OpeningGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+// Files that still need writing and the manifest to record them in
+type PreparedExtraction = (Vec<FileParam>, Arc<Manifest>);
+
 pub struct TpArchiveFileParamManager {
     path: PathBuf,
     runtime: tokio::runtime::Handle,
@@ -43,28 +79,68 @@ pub struct TpArchiveFileParamManager {
     tp_archive_file_param: TpArchiveFileParam,
     archive_params: Vec<ArchiveParam>,
     file_params: Vec<FileParam>,
-    file_params_filter: String,
-
-    archives_cache: Arc<RwLock<HashMap<String, Archive>>>,
+    // Display names by index into `file_params`, refreshed when the dictionary learns new names
+    display_names: Vec<String>,
+    dictionary_len: usize,
+    // Indices into `file_params` in table order, and the filter text and folder they were listed with
+    listing: Vec<usize>,
+    listing_key: Option<(String, String)>,
+    file_params_filter: FilterEdit,
+    file_params_lookup: String,
+    file_params_lookup_hash: Option<u32>,
+    scroll_to_lookup: bool,
+    hash_mismatch_count: usize,
+    directory_tree: DirectoryTree,
+    archive_params_table: SortableTable,
+    file_params_table: SortableTable,
+    selected_hashes: HashSet<u32>,
+    extract_options: ExtractOptions,
+    dry_run_report: Option<Vec<String>>,
+    // Anchor for shift-click range selection
+    last_clicked_hash: Option<u32>,
+
+    archives_cache: ArchivesCache,
     extracted_file_count: Arc<std::sync::RwLock<usize>>,
-    failed_extraction_count: Arc<std::sync::RwLock<usize>>
+    failed_extraction_count: Arc<std::sync::RwLock<usize>>,
+    pending_opens: Arc<std::sync::Mutex<Vec<PendingOpen>>>,
+    opening_count: Arc<std::sync::atomic::AtomicUsize>,
+
+    jobs: Jobs,
+    batch_export_dialog: BatchExportDialog,
 }
 
-impl TpArchiveFileParamManager {
-    pub fn new<R: std::io::Read + std::io::Seek>(path: PathBuf, runtime: tokio::runtime::Handle, mut reader: R) -> Result<Self, std::io::Error> {
-        let tp_archive_file_param = TpArchiveFileParam::new(&mut reader)?;
+// Reads the archive and file tables of a tpArchiveFileParam asset
+pub(crate) fn read_index<R: std::io::Read + std::io::Seek>(mut reader: R) -> Result<(TpArchiveFileParam, Vec<ArchiveParam>, Vec<FileParam>), std::io::Error> {
+    let tp_archive_file_param = TpArchiveFileParam::new(&mut reader)?;
 
-        reader.seek(std::io::SeekFrom::Start(tp_archive_file_param.offset_archives))?;
-        let mut archive_params = Vec::new();
-        for _ in 0..tp_archive_file_param.archive_count {
-            archive_params.push(ArchiveParam::new(&mut reader)?);
-        }
+    reader.seek(std::io::SeekFrom::Start(tp_archive_file_param.offset_archives))?;
+    let mut archive_params = Vec::new();
+    for _ in 0..tp_archive_file_param.archive_count {
+        archive_params.push(ArchiveParam::new(&mut reader)?);
+    }
 
-        reader.seek(std::io::SeekFrom::Start(tp_archive_file_param.offset_files))?;
-        let mut file_params = Vec::new();
-        for _ in 0..tp_archive_file_param.file_count {
-            file_params.push(FileParam::new(&mut reader)?);
+    reader.seek(std::io::SeekFrom::Start(tp_archive_file_param.offset_files))?;
+    let mut file_params = Vec::new();
+    for _ in 0..tp_archive_file_param.file_count {
+        file_params.push(FileParam::new(&mut reader)?);
+    }
+
+    Ok((tp_archive_file_param, archive_params, file_params))
+}
+
+// What the folder tree counts for each file
+fn tree_entries<'a>(display_names: &'a [String], file_params: &'a [FileParam]) -> impl Iterator<Item = (&'a str, u64, u64)> {
+    display_names.iter().zip(file_params.iter()).map(|(name, file_param)| (name.as_str(), file_param.compressed_size as u64, file_param.uncompressed_size as u64))
+}
+
+impl TpArchiveFileParamManager {
+    pub fn new<R: std::io::Read + std::io::Seek>(path: PathBuf, runtime: tokio::runtime::Handle, reader: R) -> Result<Self, std::io::Error> {
+        let (tp_archive_file_param, archive_params, file_params) = read_index(reader)?;
+        for file_param in file_params.iter() {
+            dictionary::observe(&file_param.name, file_param.hash);
         }
+        let display_names = file_params.iter().map(FileParam::display_name).collect::<Vec<_>>();
+        let directory_tree = DirectoryTree::new(tree_entries(&display_names, &file_params));
 
         Ok(Self {
             path,
@@ -73,15 +149,100 @@ impl TpArchiveFileParamManager {
             tp_archive_file_param,
             archive_params,
             file_params: file_params.clone(),
-            file_params_filter: String::new(),
-
-            archives_cache: Arc::new(RwLock::new(HashMap::new())),
+            display_names,
+            dictionary_len: dictionary::len(),
+            listing: Vec::new(),
+            listing_key: None,
+            file_params_filter: FilterEdit::default(),
+            file_params_lookup: String::new(),
+            file_params_lookup_hash: None,
+            scroll_to_lookup: false,
+            hash_mismatch_count: file_params.iter().filter(|file_param| !hash::verify(&file_param.name, file_param.hash)).count(),
+            directory_tree,
+            archive_params_table: SortableTable::new("archive_params", &["Name", "Flags", "Streamed"]),
+            file_params_table: SortableTable::new("file_params", &["", "Archive", "Path", "Hash", "Compressed Size", "Uncompressed Size", "Compressed", "Actions"]).with_unsortable(&[0, 7]),
+            selected_hashes: HashSet::new(),
+            extract_options: ExtractOptions::default(),
+            dry_run_report: None,
+            last_clicked_hash: None,
+
+            archives_cache: ArchivesCache::default(),
             extracted_file_count: Arc::new(std::sync::RwLock::new(file_params.len())),
-            failed_extraction_count: Arc::new(std::sync::RwLock::new(0))
+            failed_extraction_count: Arc::new(std::sync::RwLock::new(0)),
+            pending_opens: Arc::new(std::sync::Mutex::new(Vec::new())),
+            opening_count: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+
+            jobs: Jobs::default(),
+            batch_export_dialog: BatchExportDialog::default(),
         })
     }
 
-    fn extract_file(&self, file_param: &FileParam) -> Result<(), std::io::Error> {
+    fn find_file_param(&mut self, toasts: &mut egui_notify::Toasts) {
+        let Some(index) = self.file_params.iter().position(|file_param| hash::matches_query(&self.file_params_lookup, &file_param.name, file_param.hash)) else {
+            self.file_params_lookup_hash = None;
+            toasts.warning(format!("No file matches \"{}\".", self.file_params_lookup.trim())).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            return;
+        };
+
+        if !self.matches_filter(index) {
+            self.file_params_filter.clear();
+        }
+        self.directory_tree.reveal(&self.display_names[index]);
+        self.file_params_lookup_hash = Some(self.file_params[index].hash);
+        self.scroll_to_lookup = true;
+    }
+
+    // Whether the file at `index` passes both the text filter and the folder selected in the tree
+    fn is_visible(&self, index: usize) -> bool {
+        self.matches_filter(index) && self.directory_tree.contains(&self.display_names[index])
+    }
+
+    fn matches_filter(&self, index: usize) -> bool {
+        self.file_params_filter.is_empty() || self.file_params_filter.filter().matches(&self.file_params[index].filter_entry(&self.display_names[index], &self.archive_params))
+    }
+
+    fn visible_file_params(&self) -> impl Iterator<Item = &FileParam> + '_ {
+        (0..self.file_params.len()).filter(|index| self.is_visible(*index)).map(|index| &self.file_params[index])
+    }
+
+    // Filtering and sorting tens of thousands of entries is too slow to redo every frame,
+    // so the listing is only rebuilt when its inputs change
+    fn refresh_listing(&mut self) {
+        if dictionary::len() != self.dictionary_len {
+            self.display_names = self.file_params.iter().map(FileParam::display_name).collect();
+            self.directory_tree.set_files(tree_entries(&self.display_names, &self.file_params));
+            self.dictionary_len = dictionary::len();
+            self.listing_key = None;
+        }
+        let key = (self.file_params_filter.text().to_string(), self.directory_tree.selected().to_string());
+        if self.listing_key.as_ref() == Some(&key) {
+            return;
+        }
+
+        let mut listing = (0..self.file_params.len()).filter(|index| self.is_visible(*index)).collect::<Vec<_>>();
+        let (file_params, display_names) = (&self.file_params, &self.display_names);
+        self.file_params_table.sort(&mut listing, |a, b, column| {
+            let (file_a, file_b) = (&file_params[*a], &file_params[*b]);
+            match column {
+                1 => file_a.archive_index.cmp(&file_b.archive_index),
+                2 => display_names[*a].cmp(&display_names[*b]),
+                3 => file_a.hash.cmp(&file_b.hash),
+                4 => file_a.compressed_size.cmp(&file_b.compressed_size),
+                5 => file_a.uncompressed_size.cmp(&file_b.uncompressed_size),
+                6 => file_a.is_compressed.cmp(&file_b.is_compressed),
+                _ => std::cmp::Ordering::Equal,
+            }
+        });
+        self.listing = listing;
+        self.listing_key = Some(key);
+    }
+
+    // The row is already on screen, so unlike a lookup this leaves the tree and scroll position alone
+    fn select_file_param(&mut self, hash: u32) {
+        self.file_params_lookup_hash = Some(hash);
+    }
+
+    fn extract_file(&self, file_param: &FileParam) -> Result<WriteOutcome, std::io::Error> {
         let archive_param = &self.archive_params[file_param.archive_index as usize];
         let archive_name = archive_param.name.clone();
         let mut archives_directory = self.path.clone();
@@ -96,47 +257,244 @@ impl TpArchiveFileParamManager {
             }
         }
 
-        let offset = (file_param.archive_offset as u64) << 4;
+        let offset = file_param.offset();
 
         let archive_file = std::fs::File::open(archive_path)?;
         let archive = Archive::new(archive_file, archive_param.is_streamed)?;
 
         let file = archive.get_file(offset, file_param.compressed_size as usize, file_param.uncompressed_size as usize, file_param.buffer_size as usize, file_param.is_compressed)?;
 
-        let file_name = file_param.name.clone();
+        let file_name = file_param.display_name();
 
         let Some(output_folder) = rfd::FileDialog::new().set_title(&file_name).pick_folder() else {
             return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."));
         };
 
-        let mut output_path = output_folder.join(&file_name);
-        let output_dir = output_path.parent().ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."))?;
+        extract::write_file(&output_folder, &file_name, &file, self.extract_options)
+    }
+
+    fn open_file(&self, file_param: &FileParam) {
+        let mut archives_directory = self.path.clone();
+        archives_directory.pop();
+        let archive_param = self.archive_params[file_param.archive_index as usize].clone();
+        let archives_cache = self.archives_cache.clone();
+        let pending_opens = self.pending_opens.clone();
+        let opening_count = self.opening_count.clone();
+        let virtual_path = PathBuf::from(format!("{}!/{}", self.path.to_str().unwrap_or_default(), file_param.display_name()));
+        let file_param = file_param.clone();
+
+        let opening = OpeningGuard::new(opening_count);
+        self.runtime.spawn(async move {
+            // Dropped on every path, a panicking read must not leave the spinner running forever
+            let _opening = opening;
+            let file = TpArchiveFileParamManager::read_file_async(archives_directory, archive_param, archives_cache, &file_param).await;
+            pending_opens.lock().unwrap().push((virtual_path, file));
+        });
+    }
+
+    fn extract_folder(&mut self, folder: &str) -> Result<(), std::io::Error> {
+        let file_params = self.file_params.iter().zip(self.display_names.iter()).filter(|(_, name)| directory_tree::in_folder(folder, name)).map(|(file_param, _)| file_param.clone()).collect::<Vec<_>>();
+        self.extract_files(file_params, if folder.is_empty() { "/" } else { folder })
+    }
+
+    fn extract_selected_files(&mut self) -> Result<(), std::io::Error> {
+        let file_params = self.file_params.iter().filter(|file_param| self.selected_hashes.contains(&file_param.hash)).cloned().collect::<Vec<_>>();
+        self.extract_files(file_params, "selection")
+    }
+
+    fn extract_filtered_files(&mut self) -> Result<(), std::io::Error> {
+        let file_params = self.visible_file_params().cloned().collect::<Vec<_>>();
+        self.extract_files(file_params, "filter")
+    }
+
+    // Drops files the manifest says are up to date. Dry runs only fill the report,
+    // real runs check that everything that would be written fits first.
+    fn prepare_extraction(&mut self, output_folder: &std::path::Path, file_params: Vec<FileParam>) -> Result<Option<PreparedExtraction>, std::io::Error> {
+        let manifest = Manifest::open(output_folder)?;
+        let (current, file_params): (Vec<_>, Vec<_>) = file_params.into_iter().partition(|file_param| self.extract_options.incremental && manifest.is_current(output_folder, &file_param.manifest_entry(&self.archive_params)));
+
+        let plans = file_params.iter().map(|file_param| (file_param, extract::plan(output_folder, &file_param.display_name(), file_param.extracted_size(), self.extract_options.overwrite))).collect::<Vec<_>>();
+        if self.extract_options.dry_run {
+            let up_to_date = current.iter().map(|file_param| format!("Up to date: {}", file_param.display_name()));
+            self.dry_run_report = Some(up_to_date.chain(plans.iter().map(|(_, plan)| match plan {
+                Ok(outcome) => outcome.describe(),
+                Err(e) => format!("Error: {}", e),
+            })).collect());
+            return Ok(None);
+        }
+
+        let required = plans.iter().filter(|(_, plan)| plan.as_ref().is_ok_and(WriteOutcome::writes)).map(|(file_param, _)| file_param.extracted_size()).sum();
+        extract::check_free_space(output_folder, required)?;
+        Ok(Some((file_params, Arc::new(manifest))))
+    }
 
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir)?;
+    // Extracts every file into one chosen folder as a single job
+    fn extract_files(&mut self, file_params: Vec<FileParam>, source: &str) -> Result<(), std::io::Error> {
+        if file_params.is_empty() {
+            return Ok(());
         }
+        let Some(output_folder) = rfd::FileDialog::new().set_title(format!("Extract {} files", file_params.len())).pick_folder() else {
+            return Ok(());
+        };
+        let Some((mut file_params, manifest)) = self.prepare_extraction(&output_folder, file_params)? else {
+            return Ok(());
+        };
 
-        let mut output_file = std::fs::File::create(&mut output_path)?;
-        
-        output_file.write_all(&file)?;
-        output_file.flush()?;
+        file_params.sort_by_key(|file_param| file_param.archive_index);
+        let job = self.jobs.start(format!("Extracting {} files from {}", file_params.len(), source), file_params.len());
 
+        for file_param in file_params {
+            let output_folder = output_folder.clone();
+            let mut archives_directory = self.path.clone();
+            archives_directory.pop();
+            let archive_param = self.archive_params[file_param.archive_index as usize].clone();
+            let archives_cache = self.archives_cache.clone();
+            let options = self.extract_options;
+            let manifest = manifest.clone();
+            let manifest_entry = file_param.manifest_entry(&self.archive_params);
+            let job = job.clone();
+
+            self.runtime.spawn(async move {
+                let result = TpArchiveFileParamManager::extract_file_async(archives_directory, archive_param, archives_cache, output_folder, file_param.clone(), options).await
+                    .and_then(|outcome| if outcome.is_in_place() { manifest.record(&manifest_entry) } else { Ok(()) });
+                match result {
+                    Ok(_) => job.complete(),
+                    Err(e) => job.fail(format!("{}: {}", file_param.display_name(), e)),
+                }
+            });
+        }
         Ok(())
     }
 
-    fn extract_all_files(&self) -> Result<(), std::io::Error> {
+    // Reads the index from disk rather than from memory so the original layout and compression can be recorded
+    fn export_index_document(&self, toasts: &mut egui_notify::Toasts) {
+        let stem = self.path.file_stem().unwrap_or_default().to_str().unwrap_or_default();
+        let Some(output_path) = rfd::FileDialog::new().set_title("Export index").add_filter("JSON", &["json"]).set_file_name(format!("{}.json", stem)).save_file() else {
+            return;
+        };
+
+        let result = std::fs::read(&self.path)
+            .and_then(|data| IndexDocument::export(&data))
+            .and_then(|(document, warnings)| {
+                let json = serde_json::to_string_pretty(&document).map_err(std::io::Error::other)?;
+                std::fs::write(&output_path, json)?;
+                Ok(warnings)
+            });
+        match result {
+            Ok(warnings) => {
+                toasts.success("Index exported successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                for warning in warnings {
+                    toasts.warning(warning).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                }
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to export index: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    fn build_index_document(&self, toasts: &mut egui_notify::Toasts) {
+        let Some(document_path) = rfd::FileDialog::new().set_title("Index document").add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+        let Some(output_path) = rfd::FileDialog::new().set_title("Save index").add_filter("Index", &["arc"]).set_file_name("info.arc").save_file() else {
+            return;
+        };
+
+        let result = std::fs::read_to_string(&document_path)
+            .and_then(|json| serde_json::from_str::<IndexDocument>(&json).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+            .and_then(|document| document.build())
+            .and_then(|(data, warnings)| {
+                std::fs::write(&output_path, data)?;
+                Ok(warnings)
+            });
+        match result {
+            Ok(warnings) => {
+                toasts.success("Index rebuilt successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                for warning in warnings {
+                    toasts.warning(warning).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                }
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to rebuild index: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    fn paint_dry_run_report(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.dry_run_report else {
+            return;
+        };
+
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(format!("{} Dry run", egui_phosphor::regular::LIST_CHECKS))
+            .id(egui::Id::new(&self.path).with("dry_run_report"))
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                let writes = report.iter().filter(|line| !line.starts_with("Skip") && !line.starts_with("Unchanged") && !line.starts_with("Error")).count();
+                ui.label(format!("{} of {} files would be written.", writes, report.len()));
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(report.join("\n"));
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::both()
+                    .max_height(400.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, row_height, report.len(), |ui, row_range| {
+                        for line in &report[row_range] {
+                            ui.add(egui::Label::new(line).extend());
+                        }
+                    });
+            });
+        if !open || close {
+            self.dry_run_report = None;
+        }
+    }
+
+    // Plain clicks select one row, ctrl toggles a row and shift extends from the last clicked row
+    // `index` is a row of the listing
+    fn click_file_param(&mut self, index: usize, modifiers: egui::Modifiers) {
+        let hash = self.file_params[self.listing[index]].hash;
+        if modifiers.shift && let Some(anchor) = self.last_clicked_hash.and_then(|hash| self.listing.iter().position(|file_index| self.file_params[*file_index].hash == hash)) {
+            let range = anchor.min(index)..=anchor.max(index);
+            self.selected_hashes.extend(self.listing[range].iter().map(|file_index| self.file_params[*file_index].hash));
+            return;
+        }
+
+        if modifiers.command {
+            if !self.selected_hashes.remove(&hash) {
+                self.selected_hashes.insert(hash);
+            }
+        } else {
+            self.selected_hashes = HashSet::from([hash]);
+            self.select_file_param(hash);
+        }
+        self.last_clicked_hash = Some(hash);
+    }
+
+    fn extract_all_files(&mut self) -> Result<(), std::io::Error> {
         let Some(output_folder) = rfd::FileDialog::new().set_title("Extract all files").pick_folder() else {
             return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."));
         };
+        let Some((mut sorted_file_params, manifest)) = self.prepare_extraction(&output_folder, self.file_params.clone())? else {
+            return Ok(());
+        };
 
+        // Files that are already up to date count as extracted
         {
             let mut extracted_file_count = self.extracted_file_count.write().unwrap();
-            *extracted_file_count = 0;
+            *extracted_file_count = self.file_params.len() - sorted_file_params.len();
             let mut failed_extraction_count = self.failed_extraction_count.write().unwrap();
             *failed_extraction_count = 0;
         }
 
-        let mut sorted_file_params = self.file_params.clone();
         sorted_file_params.sort_by(|a, b| a.archive_index.cmp(&b.archive_index));
 
         for file_param in sorted_file_params.iter().cloned() {
@@ -147,16 +505,21 @@ impl TpArchiveFileParamManager {
             let archives_cache = self.archives_cache.clone();
             let extracted_file_count = self.extracted_file_count.clone();
             let failed_extraction_count = self.failed_extraction_count.clone();
+            let options = self.extract_options;
+            let manifest = manifest.clone();
+            let manifest_entry = file_param.manifest_entry(&self.archive_params);
 
             self.runtime.spawn(async move {            
-                match TpArchiveFileParamManager::extract_file_async(archives_directory, archive_param, archives_cache, output_folder, file_param.clone()).await {
+                let result = TpArchiveFileParamManager::extract_file_async(archives_directory, archive_param, archives_cache, output_folder, file_param.clone(), options).await
+                    .and_then(|outcome| if outcome.is_in_place() { manifest.record(&manifest_entry) } else { Ok(()) });
+                match result {
                     Ok(_) => {
-                        println!("Extracted {}.", file_param.name);
+                        println!("Extracted {}.", file_param.display_name());
                         let mut extracted_file_count = extracted_file_count.write().unwrap();
                         *extracted_file_count += 1;
                     },
                     Err(e) => {
-                        println!("Failed to extract {}: {}", file_param.name, e);
+                        println!("Failed to extract {}: {}", file_param.display_name(), e);
                         let mut failed_extraction_count = failed_extraction_count.write().unwrap();
                         *failed_extraction_count += 1;
                     }
@@ -168,7 +531,7 @@ impl TpArchiveFileParamManager {
     }
 
 
-    async fn extract_file_async(archives_directory: PathBuf, archive_param: ArchiveParam, archives_cache: Arc<RwLock<HashMap<String, Archive>>>, output_folder: PathBuf, file_param: FileParam) -> Result<(), std::io::Error> {
+    async fn read_file_async(archives_directory: PathBuf, archive_param: ArchiveParam, archives_cache: ArchivesCache, file_param: &FileParam) -> Result<Vec<u8>, std::io::Error> {
         let archive_name = archive_param.name.clone();
         let archive_path = archives_directory.join(&archive_name);
 
@@ -176,54 +539,103 @@ impl TpArchiveFileParamManager {
             return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Archive \"{}\" not found.", &archive_name)));
         }
 
-        let offset = (file_param.archive_offset as u64) << 4;
-        let total_size = (file_param.uncompressed_size + file_param.buffer_size) as usize;
-
-        {
-            let is_streamed = archive_param.is_streamed.clone();
-            let cache = archives_cache.clone();
-            let mut cache_write = cache.write().await;
-
-            if !cache_write.contains_key(&archive_name) {
-                println!("Opening {}...", archive_name);
-                let archive_file = std::fs::File::open(archive_path).unwrap();
-                let archive = Archive::new(archive_file, is_streamed).unwrap();
-                cache_write.insert(archive_name.clone(), archive);
-            } else {
+        let offset = file_param.offset();
+
+        // Bounded like the Vfs cache, browsing one file from every archive must not keep the whole install in memory
+        let archive = {
+            let mut cache = archives_cache.write().await;
+            match cache.iter().position(|(name, _)| *name == archive_name) {
+                Some(position) => {
+                    let entry = cache.remove(position).unwrap();
+                    let archive = entry.1.clone();
+                    cache.push_back(entry);
+                    archive
+                },
+                None => {
+                    println!("Opening {}...", archive_name);
+                    let archive_file = std::fs::File::open(archive_path)?;
+                    let archive = Arc::new(Archive::new(archive_file, archive_param.is_streamed)?);
+                    if cache.len() >= MAX_CACHED_ARCHIVES {
+                        cache.pop_front();
+                    }
+                    cache.push_back((archive_name.clone(), archive.clone()));
+                    archive
+                }
             }
-        }
+        };
 
-        let cache = archives_cache.clone();
-        let cache_read = cache.read().await;
-        let archive = cache_read.get(&archive_name).unwrap();
+        archive.get_file(offset, file_param.compressed_size as usize, file_param.uncompressed_size as usize, file_param.buffer_size as usize, file_param.is_compressed)
+    }
 
-        let file_name = file_param.name.clone();
-        let file = archive.get_file(offset, file_param.compressed_size as usize, file_param.uncompressed_size as usize, file_param.buffer_size as usize, file_param.is_compressed)?;
+    async fn extract_file_async(archives_directory: PathBuf, archive_param: ArchiveParam, archives_cache: ArchivesCache, output_folder: PathBuf, file_param: FileParam, options: ExtractOptions) -> Result<WriteOutcome, std::io::Error> {
+        let file_name = file_param.display_name();
+        // Rejects unsafe paths before anything is decompressed
+        extract::sanitize_path(&file_name)?;
+        let file = TpArchiveFileParamManager::read_file_async(archives_directory, archive_param, archives_cache, &file_param).await?;
+        extract::write_file(&output_folder, &file_name, &file, options)
+    }
 
-        let mut output_path = output_folder.join(&file_name);
-        let output_dir = output_path.parent().ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."))?;
+    fn export_textures(&mut self, options: batch::BatchExportOptions) {
+        let Some(output_folder) = rfd::FileDialog::new().set_title("Export textures").pick_folder() else {
+            return;
+        };
 
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir)?;
-        }
+        // Only PACKs hold textures, checking the name avoids decompressing every other file
+        let file_params = self.visible_file_params().filter(|file_param| file_param.display_name().ends_with(".pack")).cloned().collect::<Vec<_>>();
+        let job = self.jobs.start(format!("Exporting textures from {} packs", file_params.len()), file_params.len());
+        let workers = batch::workers();
+
+        for file_param in file_params {
+            let name = file_param.display_name();
+            let output_folder = match extract::sanitize_path(&name) {
+                Ok(relative_path) => output_folder.join(relative_path),
+                Err(e) => {
+                    job.fail(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            let mut archives_directory = self.path.clone();
+            archives_directory.pop();
+            let archive_param = self.archive_params[file_param.archive_index as usize].clone();
+            let archives_cache = self.archives_cache.clone();
+            let runtime = self.runtime.clone();
+            let workers = workers.clone();
+            let job = job.clone();
 
-        let mut output_file = std::fs::File::create(&mut output_path)?;
-        
-        output_file.write_all(&file)?;
-        output_file.flush()?;
-        Ok(())
+            self.runtime.spawn(async move {
+                let Ok(_permit) = workers.acquire_owned().await else {
+                    return;
+                };
+                let file = match TpArchiveFileParamManager::read_file_async(archives_directory, archive_param, archives_cache, &file_param).await {
+                    Ok(file) => file,
+                    Err(e) => {
+                        job.fail(format!("{}: {}", name, e));
+                        return;
+                    }
+                };
+                if !batch::is_pack(&file) {
+                    job.complete();
+                    return;
+                }
+                let handle = runtime.clone();
+                let failed_job = job.clone();
+                if let Err(e) = runtime.spawn_blocking(move || batch::export_pack_textures_job(name.into(), handle, &file, &output_folder, options, &job)).await {
+                    failed_job.fail(e.to_string());
+                }
+            });
+        }
     }
 }
 
 impl Resource for TpArchiveFileParamManager {}
 impl ResourceManager for TpArchiveFileParamManager {}
 
-struct Archive {
+pub(crate) struct Archive {
     data: Vec<u8>,
 }
 
 impl Archive {
-    fn new<R: Read + Seek>(mut reader: R, streamed: bool) -> Result<Self, std::io::Error> {
+    pub(crate) fn new<R: Read + Seek>(mut reader: R, streamed: bool) -> Result<Self, std::io::Error> {
         let data = match streamed {
             true => {
                 let mut data = Vec::new();
@@ -251,37 +663,41 @@ impl Archive {
         })
     }
 
-    fn get_file(&self, offset: u64, compressed_size: usize, uncompressed_size: usize, buffer_size: usize, compressed: bool) -> Result<Vec<u8>, std::io::Error> {
-        match compressed {
-            true => {
-                let mut reader = std::io::Cursor::new(&self.data);
-                let mut buf = vec![0; compressed_size];
-                reader.seek(std::io::SeekFrom::Start(offset))?;
-                reader.read_exact(&mut buf)?;
+    pub(crate) fn get_file(&self, offset: u64, compressed_size: usize, uncompressed_size: usize, buffer_size: usize, compressed: bool) -> Result<Vec<u8>, std::io::Error> {
+        read_archived_file(std::io::Cursor::new(&self.data), offset, compressed_size, uncompressed_size, buffer_size, compressed)
+    }
+}
 
-                let mut decoder = zstd::stream::Decoder::new(std::io::Cursor::new(buf))?;
-                let mut decompressed_data = vec![0; uncompressed_size + buffer_size];
-                decoder.read_exact(&mut decompressed_data)?;
-                Ok(decompressed_data)
-            },
-            false => {
-                let mut reader = std::io::Cursor::new(&self.data);
-                let mut buf = vec![0; uncompressed_size + buffer_size];
-                reader.seek(std::io::SeekFrom::Start(offset))?;
-                reader.read_exact(&mut buf)?;
-                Ok(buf)
-            }
+// Reads a single file at `offset` of an archive, decompressing it if needed
+pub(crate) fn read_archived_file<R: Read + Seek>(mut reader: R, offset: u64, compressed_size: usize, uncompressed_size: usize, buffer_size: usize, compressed: bool) -> Result<Vec<u8>, std::io::Error> {
+    match compressed {
+        true => {
+            let mut buf = vec![0; compressed_size];
+            reader.seek(std::io::SeekFrom::Start(offset))?;
+            reader.read_exact(&mut buf)?;
+
+            let mut decoder = zstd::stream::Decoder::new(std::io::Cursor::new(buf))?;
+            let mut decompressed_data = vec![0; uncompressed_size + buffer_size];
+            decoder.read_exact(&mut decompressed_data)?;
+            Ok(decompressed_data)
+        },
+        false => {
+            let mut buf = vec![0; uncompressed_size + buffer_size];
+            reader.seek(std::io::SeekFrom::Start(offset))?;
+            reader.read_exact(&mut buf)?;
+            Ok(buf)
         }
     }
 }
 
-#[derive(Clone)]
-struct ArchiveParam {
+#[derive(Clone, serde::Serialize)]
+pub(crate) struct ArchiveParam {
     rel_offset_name: u32,
-    flags: u32,
-    is_streamed: bool,
+    pub(crate) offset_name: u64,
+    pub(crate) flags: u32,
+    pub(crate) is_streamed: bool,
 
-    name: String,
+    pub(crate) name: String,
 }
 
 impl ArchiveParam {
@@ -303,6 +719,7 @@ impl ArchiveParam {
 
         Ok(Self {
             rel_offset_name,
+            offset_name,
             flags,
             is_streamed,
 
@@ -311,21 +728,59 @@ impl ArchiveParam {
     }
 }
 
-#[derive(Clone)]
-struct FileParam {
-    hash: u32,
+#[derive(Clone, serde::Serialize)]
+pub(crate) struct FileParam {
+    pub(crate) hash: u32,
     rel_offset_name: u32,
-    archive_offset: u32,
-    compressed_size: u32,
-    uncompressed_size: u32,
-    buffer_size: u32,
-    archive_index: u8,
-    is_compressed: bool,
-
-    name: String,
+    pub(crate) offset_name: u64,
+    pub(crate) archive_offset: u32,
+    pub(crate) compressed_size: u32,
+    pub(crate) uncompressed_size: u32,
+    pub(crate) buffer_size: u32,
+    pub(crate) archive_index: u8,
+    pub(crate) is_compressed: bool,
+
+    pub(crate) name: String,
 }
 
 impl FileParam {
+    pub(crate) fn display_name(&self) -> String {
+        dictionary::display_name(&self.name, self.hash)
+    }
+
+    pub(crate) fn filter_entry<'a>(&self, name: &'a str, archive_params: &'a [ArchiveParam]) -> FilterEntry<'a> {
+        FilterEntry {
+            path: name,
+            hash: self.hash,
+            archive: archive_params.get(self.archive_index as usize).map(|archive_param| archive_param.name.as_str()),
+            size: Some(self.uncompressed_size as u64),
+            compressed_size: Some(self.compressed_size as u64),
+            compressed: Some(self.is_compressed),
+        }
+    }
+
+    pub(crate) fn manifest_entry(&self, archive_params: &[ArchiveParam]) -> ManifestEntry {
+        ManifestEntry {
+            name: self.display_name(),
+            hash: self.hash,
+            archive: archive_params.get(self.archive_index as usize).map(|archive_param| archive_param.name.clone()).unwrap_or_default(),
+            offset: self.offset(),
+            compressed_size: self.compressed_size as u64,
+            uncompressed_size: self.uncompressed_size as u64,
+            buffer_size: self.buffer_size as u64,
+        }
+    }
+
+    // What extraction writes: the file and the buffer stored after it, as read_archived_file returns them
+    pub(crate) fn extracted_size(&self) -> u64 {
+        self.uncompressed_size as u64 + self.buffer_size as u64
+    }
+
+    // Offsets are stored in 16 byte units
+    pub(crate) fn offset(&self) -> u64 {
+        (self.archive_offset as u64) << 4
+    }
+
     pub fn new<R: std::io::Read + std::io::Seek>(mut reader: R) -> Result<Self, std::io::Error> {
         // Align to 4 bytes
         let offset = reader.stream_position()? % 4;
@@ -350,6 +805,7 @@ impl FileParam {
         Ok(Self {
             hash,
             rel_offset_name,
+            offset_name,
             archive_offset,
             compressed_size,
             uncompressed_size,
@@ -362,11 +818,37 @@ impl FileParam {
     }
 }
 
+fn toast_extraction_error(toasts: &mut egui_notify::Toasts, result: Result<(), std::io::Error>) {
+    if let Err(e) = result {
+        toasts.error(format!("Failed to extract files: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+    }
+}
+
+pub fn paint_hash_warning(ui: &mut eframe::egui::Ui, path: &str, hash: u32) {
+    if hash::verify(path, hash) {
+        return;
+    }
+    let hover_text = match dictionary::resolve(hash) {
+        Some(name) => format!("Expected {:08X} for this path. The stored hash belongs to {}.", hash::hash_path(path), name),
+        None => format!("Expected {:08X} for this path.", hash::hash_path(path)),
+    };
+    ui.colored_label(ui.visuals().warn_fg_color, egui_phosphor::regular::WARNING).on_hover_text(hover_text);
+}
+
 impl Manager for TpArchiveFileParamManager {
     fn path(&self) -> &PathBuf {
         &self.path
     }
 
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        let data_export = DataExport::new(&serde_json::json!({
+            "header": &self.tp_archive_file_param,
+            "archives": &self.archive_params,
+            "files": &self.file_params,
+        }))?;
+        Ok(Some(data_export.with_table("archives", self.archive_params.iter())?.with_table("files", self.file_params.iter())?))
+    }
+
     fn paint(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         egui::Frame::window(&ui.style()).show(ui, |ui| {
             egui::CollapsingHeader::new(egui::RichText::new(format!("{} tpArchiveFileParam", egui_phosphor::regular::DATABASE)).heading())
@@ -378,36 +860,22 @@ impl Manager for TpArchiveFileParamManager {
                     ui.separator();
 
                     ui.collapsing(egui::RichText::new(format!("{} Archives", egui_phosphor::regular::ARCHIVE)).heading(), |ui| {
-                        egui_extras::TableBuilder::new(ui)
-                        .id_salt("archive_params")
-                        .striped(true)
-                        .resizable(true)
-                        .columns(egui_extras::Column::auto(), 3)
-                        .header(16.0, |mut header| {
-                            header.col(|ui| {
-                                ui.heading("Name");
-                            });
-                            header.col(|ui| {
-                                ui.heading("Flags");
-                            });
-                            header.col(|ui| {
-                                ui.heading("Streamed");
-                            });
-                        })
-                        .body(|mut body| {
-                            for archive_param in self.archive_params.iter() {
-                                body.row(16.0, |mut row| {
-                                    row.col(|ui| {
-                                        ui.label(format!("{}", archive_param.name));
-                                    });
-                                    row.col(|ui| {
-                                        ui.label(format!("{}", archive_param.flags));
-                                    });
-                                    row.col(|ui| {
-                                        ui.label(format!("{}", archive_param.is_streamed));
-                                    });
-                                });
+                        let mut archive_params = self.archive_params.iter().collect::<Vec<_>>();
+                        self.archive_params_table.sort(&mut archive_params, |a, b, column| match column {
+                            0 => a.name.cmp(&b.name),
+                            1 => a.flags.cmp(&b.flags),
+                            _ => a.is_streamed.cmp(&b.is_streamed),
+                        });
+                        let archive_text = |index: usize, column: usize| {
+                            let archive_param = archive_params[index];
+                            match column {
+                                0 => archive_param.name.clone(),
+                                1 => archive_param.flags.to_string(),
+                                _ => archive_param.is_streamed.to_string(),
                             }
+                        };
+                        self.archive_params_table.show(ui, archive_params.len(), None, |_| false, archive_text, |ui, index, column| {
+                            ui.label(archive_text(index, column));
                         });
                     });
                     
@@ -416,97 +884,162 @@ impl Manager for TpArchiveFileParamManager {
                     ui.collapsing(egui::RichText::new(format!("{} Files", egui_phosphor::regular::FILES)).heading(), |ui| {
                         ui.horizontal(|ui| {
                             ui.label("Filter:");
-                            ui.text_edit_singleline(&mut self.file_params_filter);
+                            self.file_params_filter.show(ui);
+                            ui.separator();
+                            ui.label("Find path or hash:");
+                            let response = ui.text_edit_singleline(&mut self.file_params_lookup);
+                            if (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) || ui.button(egui_phosphor::regular::MAGNIFYING_GLASS).clicked() {
+                                self.find_file_param(toasts);
+                            }
                         });
-
-                        egui_extras::TableBuilder::new(ui)
-                        .id_salt("file_params")
-                        .striped(true)
-                        .resizable(true)
-                        .columns(egui_extras::Column::auto(), 7)
-                        .header(16.0, |mut header: egui_extras::TableRow<'_, '_>| {
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Archive").clicked() {
-                                    self.file_params.sort_by(|a, b| a.archive_index.cmp(&b.archive_index));
-                                }
-                            });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Path").clicked() {
-                                    self.file_params.sort_by(|a, b| a.name.cmp(&b.name));
-                                }
-                            });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Hash").clicked() {
-                                    self.file_params.sort_by(|a, b| a.hash.cmp(&b.hash));
+                        if self.hash_mismatch_count > 0 {
+                            ui.colored_label(ui.visuals().warn_fg_color, format!("{} {} files have a hash that does not match their path.", egui_phosphor::regular::WARNING, self.hash_mismatch_count));
+                        }
+
+                        ui.collapsing(format!("{} Folders", egui_phosphor::regular::TREE_VIEW), |ui| {
+                            egui::ScrollArea::vertical()
+                                .id_salt("directory_tree")
+                                .max_height(240.0)
+                                .auto_shrink([false, true])
+                                .show(ui, |ui| {
+                                    if let Some(DirectoryTreeAction::Extract(folder)) = self.directory_tree.show(ui, egui::Id::new(&self.path).with("directory_tree")) {
+                                        let result = self.extract_folder(&folder);
+                                        toast_extraction_error(toasts, result);
+                                    }
+                                });
+                        });
+                        if !self.directory_tree.selected().is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{} {}", egui_phosphor::regular::FOLDER_OPEN, self.directory_tree.selected()));
+                                if ui.small_button(egui_phosphor::regular::X).on_hover_text("Show all folders").clicked() {
+                                    self.directory_tree.reveal("");
                                 }
                             });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Compressed Size").clicked() {
-                                    self.file_params.sort_by(|a, b| a.compressed_size.cmp(&b.compressed_size));
+                        }
+
+                        self.refresh_listing();
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} of {} files selected", self.selected_hashes.len(), self.file_params.len()));
+                            if ui.button("Select filtered").clicked() {
+                                self.selected_hashes.extend(self.listing.iter().map(|index| self.file_params[*index].hash));
+                            }
+                            if ui.add_enabled(!self.selected_hashes.is_empty(), egui::Button::new("Clear selection")).clicked() {
+                                self.selected_hashes.clear();
+                            }
+                            if ui.add_enabled(!self.selected_hashes.is_empty(), egui::Button::new("Extract selected…")).clicked() {
+                                let result = self.extract_selected_files();
+                                toast_extraction_error(toasts, result);
+                            }
+                            if ui.add_enabled(!self.listing.is_empty(), egui::Button::new("Extract filtered…")).clicked() {
+                                let result = self.extract_filtered_files();
+                                toast_extraction_error(toasts, result);
+                            }
+                        });
+
+                        let scroll_to_row = match std::mem::take(&mut self.scroll_to_lookup) {
+                            true => self.listing.iter().position(|index| Some(self.file_params[*index].hash) == self.file_params_lookup_hash),
+                            false => None,
+                        };
+
+                        let mut open_request = None;
+                        let mut extract_request = None;
+                        let archive_params = &self.archive_params;
+                        let (file_params, display_names, listing) = (&self.file_params, &self.display_names, &self.listing);
+                        let selected_hashes = &mut self.selected_hashes;
+                        let last_clicked_hash = &mut self.last_clicked_hash;
+                        let lookup_hash = self.file_params_lookup_hash;
+                        let selection = selected_hashes.clone();
+                        let response = self.file_params_table.show(
+                            ui,
+                            listing.len(),
+                            scroll_to_row,
+                            |index| Some(file_params[listing[index]].hash) == lookup_hash || selection.contains(&file_params[listing[index]].hash),
+                            |index, column| {
+                                let file_param = &file_params[listing[index]];
+                                match column {
+                                    1 => archive_params[file_param.archive_index as usize].name.clone(),
+                                    2 => display_names[listing[index]].clone(),
+                                    3 => format!("{:08X}", file_param.hash),
+                                    4 => file_param.compressed_size.to_string(),
+                                    5 => file_param.uncompressed_size.to_string(),
+                                    6 => file_param.is_compressed.to_string(),
+                                    _ => String::new(),
                                 }
-                            });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Uncompressed Size").clicked() {
-                                    self.file_params.sort_by(|a, b| a.uncompressed_size.cmp(&b.uncompressed_size));
+                            },
+                            |ui, index, column| {
+                                let file_param = &file_params[listing[index]];
+                                match column {
+                                    0 => {
+                                        let mut selected = selected_hashes.contains(&file_param.hash);
+                                        if ui.checkbox(&mut selected, "").changed() {
+                                            match selected {
+                                                true => selected_hashes.insert(file_param.hash),
+                                                false => selected_hashes.remove(&file_param.hash),
+                                            };
+                                            *last_clicked_hash = Some(file_param.hash);
+                                        }
+                                    },
+                                    1 => {
+                                        ui.add(egui::Label::new(&archive_params[file_param.archive_index as usize].name).wrap_mode(egui::TextWrapMode::Extend));
+                                    },
+                                    2 => {
+                                        match file_param.name.is_empty() {
+                                            true => ui.add(egui::Label::new(egui::RichText::new(&display_names[listing[index]]).italics()).wrap_mode(egui::TextWrapMode::Extend)),
+                                            false => ui.add(egui::Label::new(&file_param.name).wrap_mode(egui::TextWrapMode::Extend)),
+                                        };
+                                    },
+                                    3 => {
+                                        ui.style_mut().override_font_id = Some(egui::FontId::monospace(12.0));
+                                        ui.add(egui::Label::new(format!("{:08X}", file_param.hash)).wrap_mode(egui::TextWrapMode::Extend));
+                                        paint_hash_warning(ui, &file_param.name, file_param.hash);
+                                    },
+                                    4 => {
+                                        ui.label(file_param.compressed_size.to_string());
+                                    },
+                                    5 => {
+                                        ui.label(file_param.uncompressed_size.to_string());
+                                    },
+                                    6 => {
+                                        ui.label(file_param.is_compressed.to_string());
+                                    },
+                                    _ => {
+                                        ui.horizontal(|ui| {
+                                            if ui.button("Open").clicked() {
+                                                open_request = Some(file_param.clone());
+                                            }
+                                            if ui.button("Extract").clicked() {
+                                                extract_request = Some(file_param.clone());
+                                            }
+                                        });
+                                    },
                                 }
-                            });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                if ui.heading("Compressed").clicked() {
-                                    self.file_params.sort_by(|a, b| a.is_compressed.cmp(&b.is_compressed));
+                            },
+                        );
+
+                        if let Some(file_param) = open_request {
+                            self.open_file(&file_param);
+                        }
+                        if let Some(file_param) = extract_request {
+                            match self.extract_file(&file_param) {
+                                Ok(outcome) => {
+                                    let message = match self.extract_options.dry_run {
+                                        true => format!("Dry run, would {}", outcome.describe().to_lowercase()),
+                                        false => outcome.describe(),
+                                    };
+                                    toasts.success(message).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                                },
+                                Err(e) => {
+                                    toasts.error(format!("Failed to extract file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
                                 }
-                            });
-                            header.col(|ui| {
-                                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                ui.heading("Extract");
-                            });
-                        })
-                        .body(|body| {
-                            let filtered_file_params = &self.file_params.iter().filter(|file_param| self.file_params_filter.is_empty() || file_param.name.contains(&self.file_params_filter)).cloned().collect::<Vec<_>>();
-                            body.rows(16.0, filtered_file_params.len(), |mut row| {
-                                let file_param = &filtered_file_params[row.index()];
-                                let archive_param = &self.archive_params[file_param.archive_index as usize];
-
-                                row.col(|ui| {
-                                    ui.add(egui::Label::new(format!("{}", archive_param.name)).wrap_mode(egui::TextWrapMode::Extend));
-                                });
-                                row.col(|ui| {
-                                    ui.add(egui::Label::new(format!("{}", file_param.name)).wrap_mode(egui::TextWrapMode::Extend));
-                                });
-                                row.col(|ui| {
-                                    ui.style_mut().override_font_id = Some(egui::FontId::monospace(12.0));
-                                    ui.add(egui::Label::new(format!("{:08X}", file_param.hash)).wrap_mode(egui::TextWrapMode::Extend));
-                                });
-                                row.col(|ui| {
-                                    ui.label(format!("{}", file_param.compressed_size));
-                                });
-                                row.col(|ui| {
-                                    ui.label(format!("{}", file_param.uncompressed_size));
-                                });
-                                row.col(|ui| {
-                                    ui.label(format!("{}", file_param.is_compressed));
-                                });
-                                row.col(|ui| {
-                                    ui.centered_and_justified(|ui| {
-                                        if ui.button("Extract").clicked() {
-                                            match self.extract_file(&file_param) {
-                                                Ok(_) => {
-                                                    toasts.success(format!("File extracted successfully.")).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                                                },
-                                                Err(e) => {
-                                                    toasts.error(format!("Failed to extract file: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                                                }
-                                            }
-                                        }
-                                    });
-                                });
-                            });
-                        });
+                            }
+                        }
+                        if let Some(index) = response.clicked_row {
+                            self.click_file_param(index, response.modifiers);
+                        }
+                        if response.sort_changed {
+                            self.listing_key = None;
+                            ui.ctx().request_repaint();
+                        }
                     });
                 });
             });
@@ -523,10 +1056,62 @@ impl Manager for TpArchiveFileParamManager {
                 }
                 ui.close_menu();
             }
+            if ui.add_enabled(!self.selected_hashes.is_empty(), egui::Button::new(format!("Selected files ({})…", self.selected_hashes.len()))).clicked() {
+                let result = self.extract_selected_files();
+                toast_extraction_error(toasts, result);
+                ui.close_menu();
+            }
+            if ui.button("Filtered files…").clicked() {
+                let result = self.extract_filtered_files();
+                toast_extraction_error(toasts, result);
+                ui.close_menu();
+            }
+
+            ui.separator();
+            ui.label("Existing files:");
+            for policy in OverwritePolicy::ALL {
+                ui.radio_value(&mut self.extract_options.overwrite, policy, policy.name());
+            }
+            ui.checkbox(&mut self.extract_options.incremental, "Skip up to date files").on_hover_text(format!("Skip files that {} in the output folder lists as extracted from the same entry", crate::manifest::MANIFEST_FILE_NAME));
+            ui.checkbox(&mut self.extract_options.dry_run, "Dry run").on_hover_text("Only report what would be written");
+        });
+
+        ui.menu_button(format!("{} Textures", egui_phosphor::regular::IMAGES), |ui| {
+            if ui.button("Export from filtered packs…").clicked() {
+                self.batch_export_dialog.open();
+                ui.close_menu();
+            }
+        });
+
+        ui.menu_button(format!("{} Index", egui_phosphor::regular::BRACKETS_CURLY), |ui| {
+            if ui.button("Export editable JSON…").clicked() {
+                self.export_index_document(toasts);
+                ui.close_menu();
+            }
+            if ui.button("Rebuild from JSON…").clicked() {
+                self.build_index_document(toasts);
+                ui.close_menu();
+            }
         });
     }
 
     fn paint_floating(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
+        let source = match self.file_params_filter.is_empty() {
+            true => format!("All packs in {}", self.path.to_str().unwrap_or_default()),
+            false => format!("Packs matching \"{}\" in {}", self.file_params_filter.text(), self.path.to_str().unwrap_or_default()),
+        };
+        if let Some(options) = self.batch_export_dialog.show(ui.ctx(), egui::Id::new(&self.path).with("batch_export"), &source) {
+            self.export_textures(options);
+        }
+        self.jobs.paint(ui.ctx());
+
+        self.paint_dry_run_report(ui.ctx());
+
+        // Keep polling until every opened entry has been read
+        if self.opening_count.load(std::sync::atomic::Ordering::SeqCst) > 0 {
+            ui.ctx().request_repaint();
+        }
+
         let extracted_file_count = self.extracted_file_count.read().unwrap();
         let failed_extraction_count = self.failed_extraction_count.read().unwrap();
         if *extracted_file_count < self.file_params.len() {
@@ -545,4 +1130,8 @@ impl Manager for TpArchiveFileParamManager {
             ui.ctx().request_repaint();
         }
     }
+
+    fn take_pending_opens(&mut self) -> Vec<PendingOpen> {
+        std::mem::take(&mut *self.pending_opens.lock().unwrap())
+    }
 }
\ No newline at end of file
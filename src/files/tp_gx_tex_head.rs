@@ -1,11 +1,16 @@
-use std::{borrow::Cow, io::Write, path::PathBuf};
+use std::{io::Write, path::PathBuf};
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use eframe::egui;
 
+use crate::texture::{alpha::{self, AlphaMode}, compare::{self, CompareSlot, TextureSnapshot}, dds, export::{ExportFormat, ExportMips}, ktx2, normal_map};
+use crate::data_export::DataExport;
 use crate::traits::*;
 use crate::util::ReadUtilExt;
+use crate::widgets::data_viewer::{ComponentType, DataTexels, DataViewer};
+use crate::widgets::texture_viewer::{RawTexels, TextureViewer, ViewerImage};
 
 #[repr(u32)]
+#[derive(serde::Serialize)]
 enum XonSurfaceDXGIFormat {
     UNKNOWN(u32),
 	R8g8b8a8UnormStraight= 0x00010700,
@@ -97,14 +102,161 @@ impl XonSurfaceDXGIFormat {
         }
     }
 
+    pub fn name(&self) -> String {
+        match self {
+            XonSurfaceDXGIFormat::UNKNOWN(value) => format!("Unknown ({:X})", value),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => "R8G8B8A8_UNORM (straight)".to_string(),
+            XonSurfaceDXGIFormat::R8g8b8a8Unorm => "R8G8B8A8_UNORM".to_string(),
+            XonSurfaceDXGIFormat::R8Unorm => "R8_UNORM".to_string(),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb => "R8G8B8A8_UNORM_SRGB".to_string(),
+            XonSurfaceDXGIFormat::Bc1Unorm => "BC1_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc1UnormSrgb => "BC1_UNORM_SRGB".to_string(),
+            XonSurfaceDXGIFormat::Bc2Unorm => "BC2_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc2UnormSrgb => "BC2_UNORM_SRGB".to_string(),
+            XonSurfaceDXGIFormat::Bc3Unorm => "BC3_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc3UnormSrgb => "BC3_UNORM_SRGB".to_string(),
+            XonSurfaceDXGIFormat::Bc4Unorm => "BC4_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc5Unorm => "BC5_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc7Unorm => "BC7_UNORM".to_string(),
+            XonSurfaceDXGIFormat::Bc1UnormVolume => "BC1_UNORM (volume)".to_string(),
+            XonSurfaceDXGIFormat::Bc7UnormSrgb => "BC7_UNORM_SRGB".to_string(),
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => "R32G32B32A32".to_string(),
+            XonSurfaceDXGIFormat::Bc6hUf16 => "BC6H_UF16".to_string(),
+        }
+    }
+
     pub fn get_alpha_mode(&self) -> u32 {
         match self {
             XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => 1,
             _ => 2
         }
     }
+
+    pub fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::from_dds(self.get_alpha_mode())
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        matches!(self,
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight | XonSurfaceDXGIFormat::R8g8b8a8Unorm | XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb |
+            XonSurfaceDXGIFormat::Bc1Unorm | XonSurfaceDXGIFormat::Bc1UnormSrgb | XonSurfaceDXGIFormat::Bc1UnormVolume |
+            XonSurfaceDXGIFormat::Bc2Unorm | XonSurfaceDXGIFormat::Bc2UnormSrgb |
+            XonSurfaceDXGIFormat::Bc3Unorm | XonSurfaceDXGIFormat::Bc3UnormSrgb |
+            XonSurfaceDXGIFormat::Bc7Unorm | XonSurfaceDXGIFormat::Bc7UnormSrgb
+        )
+    }
+
+    pub fn to_image_format(&self) -> Option<image_dds::ImageFormat> {
+        match self {
+            XonSurfaceDXGIFormat::UNKNOWN(_) => None,
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => Some(image_dds::ImageFormat::Rgba8Unorm),
+            XonSurfaceDXGIFormat::R8g8b8a8Unorm => Some(image_dds::ImageFormat::Rgba8Unorm),
+            XonSurfaceDXGIFormat::R8Unorm => Some(image_dds::ImageFormat::R8Unorm),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb => Some(image_dds::ImageFormat::Rgba8UnormSrgb),
+            XonSurfaceDXGIFormat::Bc1Unorm => Some(image_dds::ImageFormat::BC1RgbaUnorm),
+            XonSurfaceDXGIFormat::Bc1UnormSrgb => Some(image_dds::ImageFormat::BC1RgbaUnormSrgb),
+            XonSurfaceDXGIFormat::Bc2Unorm => Some(image_dds::ImageFormat::BC2RgbaUnorm),
+            XonSurfaceDXGIFormat::Bc2UnormSrgb => Some(image_dds::ImageFormat::BC2RgbaUnormSrgb),
+            XonSurfaceDXGIFormat::Bc3Unorm => Some(image_dds::ImageFormat::BC3RgbaUnorm),
+            XonSurfaceDXGIFormat::Bc3UnormSrgb => Some(image_dds::ImageFormat::BC3RgbaUnormSrgb),
+            XonSurfaceDXGIFormat::Bc4Unorm => Some(image_dds::ImageFormat::BC4RUnorm),
+            XonSurfaceDXGIFormat::Bc5Unorm => Some(image_dds::ImageFormat::BC5RgUnorm),
+            XonSurfaceDXGIFormat::Bc7Unorm => Some(image_dds::ImageFormat::BC7RgbaUnorm),
+            XonSurfaceDXGIFormat::Bc1UnormVolume => None,
+            XonSurfaceDXGIFormat::Bc7UnormSrgb => Some(image_dds::ImageFormat::BC7RgbaUnormSrgb),
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => Some(image_dds::ImageFormat::Rgba32Float),
+            XonSurfaceDXGIFormat::Bc6hUf16 => Some(image_dds::ImageFormat::BC6hRgbUfloat),
+        }
+    }
+
+    pub fn to_vk_format(&self) -> Option<u32> {
+        match self {
+            XonSurfaceDXGIFormat::UNKNOWN(_) => None,
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => Some(ktx2::VK_FORMAT_R8G8B8A8_UNORM),
+            XonSurfaceDXGIFormat::R8g8b8a8Unorm => Some(ktx2::VK_FORMAT_R8G8B8A8_UNORM),
+            XonSurfaceDXGIFormat::R8Unorm => Some(ktx2::VK_FORMAT_R8_UNORM),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb => Some(ktx2::VK_FORMAT_R8G8B8A8_SRGB),
+            XonSurfaceDXGIFormat::Bc1Unorm => Some(ktx2::VK_FORMAT_BC1_RGBA_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc1UnormSrgb => Some(ktx2::VK_FORMAT_BC1_RGBA_SRGB_BLOCK),
+            XonSurfaceDXGIFormat::Bc2Unorm => Some(ktx2::VK_FORMAT_BC2_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc2UnormSrgb => Some(ktx2::VK_FORMAT_BC2_SRGB_BLOCK),
+            XonSurfaceDXGIFormat::Bc3Unorm => Some(ktx2::VK_FORMAT_BC3_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc3UnormSrgb => Some(ktx2::VK_FORMAT_BC3_SRGB_BLOCK),
+            XonSurfaceDXGIFormat::Bc4Unorm => Some(ktx2::VK_FORMAT_BC4_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc5Unorm => Some(ktx2::VK_FORMAT_BC5_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc7Unorm => Some(ktx2::VK_FORMAT_BC7_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc1UnormVolume => Some(ktx2::VK_FORMAT_BC1_RGBA_UNORM_BLOCK),
+            XonSurfaceDXGIFormat::Bc7UnormSrgb => Some(ktx2::VK_FORMAT_BC7_SRGB_BLOCK),
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => Some(ktx2::VK_FORMAT_R32G32B32A32_SFLOAT),
+            XonSurfaceDXGIFormat::Bc6hUf16 => Some(ktx2::VK_FORMAT_BC6H_UFLOAT_BLOCK),
+        }
+    }
+
+    pub fn to_legacy_pixel_format(&self) -> Option<dds::LegacyPixelFormat> {
+        match self {
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => Some(dds::LegacyPixelFormat::Rgba8),
+            XonSurfaceDXGIFormat::R8g8b8a8Unorm => Some(dds::LegacyPixelFormat::Rgba8),
+            XonSurfaceDXGIFormat::R8Unorm => Some(dds::LegacyPixelFormat::Luminance8),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb => Some(dds::LegacyPixelFormat::Rgba8),
+            XonSurfaceDXGIFormat::Bc1Unorm => Some(dds::LegacyPixelFormat::FourCC(*b"DXT1")),
+            XonSurfaceDXGIFormat::Bc1UnormSrgb => Some(dds::LegacyPixelFormat::FourCC(*b"DXT1")),
+            XonSurfaceDXGIFormat::Bc2Unorm => Some(dds::LegacyPixelFormat::FourCC(*b"DXT3")),
+            XonSurfaceDXGIFormat::Bc2UnormSrgb => Some(dds::LegacyPixelFormat::FourCC(*b"DXT3")),
+            XonSurfaceDXGIFormat::Bc3Unorm => Some(dds::LegacyPixelFormat::FourCC(*b"DXT5")),
+            XonSurfaceDXGIFormat::Bc3UnormSrgb => Some(dds::LegacyPixelFormat::FourCC(*b"DXT5")),
+            XonSurfaceDXGIFormat::Bc4Unorm => Some(dds::LegacyPixelFormat::FourCC(*b"ATI1")),
+            XonSurfaceDXGIFormat::Bc5Unorm => Some(dds::LegacyPixelFormat::FourCC(*b"ATI2")),
+            XonSurfaceDXGIFormat::Bc1UnormVolume => Some(dds::LegacyPixelFormat::FourCC(*b"DXT1")),
+            // D3DFMT_A32B32G32R32F
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => Some(dds::LegacyPixelFormat::D3dFormat(116)),
+            _ => None,
+        }
+    }
+
+    pub fn is_normal_map(&self) -> bool {
+        matches!(self, XonSurfaceDXGIFormat::Bc5Unorm)
+    }
+
+    // (channels, component type) of formats that usually hold lookup or index data rather than colors
+    pub fn get_data_layout(&self) -> Option<(usize, ComponentType)> {
+        match self {
+            XonSurfaceDXGIFormat::R8Unorm => Some((1, ComponentType::U8)),
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => Some((4, ComponentType::U32)),
+            _ => None,
+        }
+    }
+
+    // R32G32B32A32 holds floats, as to_image_format decodes it
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, XonSurfaceDXGIFormat::Bc6hUf16 | XonSurfaceDXGIFormat::R32g32b32a32Uint)
+    }
+
+    // (block dimension, bytes per block)
+    pub fn get_block_info(&self) -> (u32, u32) {
+        match self {
+            XonSurfaceDXGIFormat::UNKNOWN(_) => (1, 4),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormStraight => (1, 4),
+            XonSurfaceDXGIFormat::R8g8b8a8Unorm => (1, 4),
+            XonSurfaceDXGIFormat::R8Unorm => (1, 1),
+            XonSurfaceDXGIFormat::R8g8b8a8UnormSrgb => (1, 4),
+            XonSurfaceDXGIFormat::Bc1Unorm => (4, 8),
+            XonSurfaceDXGIFormat::Bc1UnormSrgb => (4, 8),
+            XonSurfaceDXGIFormat::Bc2Unorm => (4, 16),
+            XonSurfaceDXGIFormat::Bc2UnormSrgb => (4, 16),
+            XonSurfaceDXGIFormat::Bc3Unorm => (4, 16),
+            XonSurfaceDXGIFormat::Bc3UnormSrgb => (4, 16),
+            XonSurfaceDXGIFormat::Bc4Unorm => (4, 8),
+            XonSurfaceDXGIFormat::Bc5Unorm => (4, 16),
+            XonSurfaceDXGIFormat::Bc7Unorm => (4, 16),
+            XonSurfaceDXGIFormat::Bc1UnormVolume => (4, 8),
+            XonSurfaceDXGIFormat::Bc7UnormSrgb => (4, 16),
+            XonSurfaceDXGIFormat::R32g32b32a32Uint => (1, 16),
+            XonSurfaceDXGIFormat::Bc6hUf16 => (4, 16),
+        }
+    }
 }
 
+#[derive(serde::Serialize)]
 struct TpGxTexHead {
     width: u32,
     height: u32,
@@ -151,6 +303,25 @@ impl TpGxTexHead {
             surfaces,
         })
     }
+
+    // Surfaces record where each mip sits in the resource, including any padding between them.
+    // Tightly packed sizes are only used when the table has no entry for the mip.
+    pub fn get_mip_range(&self, mip: u32) -> std::ops::Range<usize> {
+        if let Some(surface) = self.surfaces.get(mip as usize) && surface.size > 0 {
+            return surface.offset as usize..surface.offset as usize + surface.size as usize;
+        }
+
+        let (block_dimension, bytes_per_block) = self.format.get_block_info();
+        let mip_size = |mip: u32| {
+            let width = (self.width >> mip).max(1).div_ceil(block_dimension);
+            let height = (self.height >> mip).max(1).div_ceil(block_dimension);
+            let depth = (self.depth >> mip).max(1);
+            (width * height * depth * bytes_per_block) as usize
+        };
+
+        let start = (0..mip).map(mip_size).sum::<usize>();
+        start..start + mip_size(mip)
+    }
 }
 
 pub struct TpGxTexHeadManager {
@@ -160,13 +331,30 @@ pub struct TpGxTexHeadManager {
     tp_gx_tex_head: TpGxTexHead,
     resource: Vec<u8>,
     dds_bytes: Vec<u8>,
-    png_images: Vec<Vec<u8>>,
-    selected_png_index: usize,
+    mip_images: Vec<image::RgbaImage>,
+    hdr_mip_images: Vec<image::Rgba32FImage>,
+    normal_mip_images: Vec<image::RgbaImage>,
+    mip_images_decoded: bool,
+    reconstruct_normal_z: bool,
+    selected_mip_index: usize,
+    viewer: TextureViewer,
+    data_viewer: DataViewer,
+    show_data: bool,
+
+    export_dialog_open: bool,
+    export_format: ExportFormat,
+    export_all_mips: bool,
+    // Exported images are unpremultiplied and imported images are premultiplied again.
+    // Off by default since most formats are only flagged premultiplied, not known to hold premultiplied data.
+    convert_straight_alpha: bool,
 }
 
 impl TpGxTexHeadManager {
     pub fn new<R: std::io::Read + std::io::Seek>(path: PathBuf, runtime: tokio::runtime::Handle, reader: R) -> Result<Self, std::io::Error> {
         let tp_gx_tex_head = TpGxTexHead::new(reader)?;
+        let viewer = TextureViewer::new(egui::Id::new(&path).with("texture_viewer"));
+        let data_viewer = DataViewer::new(egui::Id::new(&path).with("data_viewer"));
+        let show_data = tp_gx_tex_head.format.get_data_layout().is_some();
 
         Ok(Self {
             path,
@@ -175,18 +363,43 @@ impl TpGxTexHeadManager {
             tp_gx_tex_head,
             resource: Vec::new(),
             dds_bytes: Vec::new(),
-            png_images: Vec::new(),
-            selected_png_index: 0,
+            mip_images: Vec::new(),
+            hdr_mip_images: Vec::new(),
+            normal_mip_images: Vec::new(),
+            mip_images_decoded: false,
+            reconstruct_normal_z: true,
+            selected_mip_index: 0,
+            viewer,
+            data_viewer,
+            show_data,
+
+            export_dialog_open: false,
+            export_format: ExportFormat::Dds,
+            export_all_mips: false,
+            convert_straight_alpha: false,
         })
     }
 
     fn populate_dds_bytes(&mut self) -> Result<(), std::io::Error> {
+        self.dds_bytes = self.build_dds_bytes(self.tp_gx_tex_head.surface_count)?;
+        Ok(())
+    }
+
+    fn build_dds_bytes(&self, mip_count: u32) -> Result<Vec<u8>, std::io::Error> {
+        let data = match mip_count < self.tp_gx_tex_head.surface_count {
+            true => {
+                let end = self.tp_gx_tex_head.get_mip_range(mip_count - 1).end;
+                self.resource.get(..end).ok_or(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Resource is smaller than its mips."))?
+            },
+            false => &self.resource[..],
+        };
+
         let mut dds_bytes = Vec::new();
         // Header
-        dds_bytes.write(b"DDS\x20")?;
+        dds_bytes.write_all(b"DDS\x20")?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(124)?;
         let mut flags = 0x1 | 0x2 | 0x4 | 0x1000 | 0x80000;
-        if self.tp_gx_tex_head.surface_count > 1 {
+        if mip_count > 1 {
             flags |= 0x20000;
         }
         if self.tp_gx_tex_head.depth > 1 {
@@ -195,9 +408,9 @@ impl TpGxTexHeadManager {
         dds_bytes.write_u32::<byteorder::LittleEndian>(flags)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.height)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.width)?;
-        dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.size)?;
+        dds_bytes.write_u32::<byteorder::LittleEndian>(data.len() as u32)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.depth)?;
-        dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.surface_count)?;
+        dds_bytes.write_u32::<byteorder::LittleEndian>(mip_count)?;
         for i in 0..11 {
             dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
         }
@@ -205,13 +418,13 @@ impl TpGxTexHeadManager {
         // DDS Pixel Format
         dds_bytes.write_u32::<byteorder::LittleEndian>(32)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(4)?;
-        dds_bytes.write(b"DX10")?;
+        dds_bytes.write_all(b"DX10")?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
         for i in 0..4{
             dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
         }
         let mut caps = 0x1000;
-        if self.tp_gx_tex_head.surface_count > 1 {
+        if mip_count > 1 {
             caps |= 0x8 | 0x400000;
         }
         dds_bytes.write_u32::<byteorder::LittleEndian>(caps)?;
@@ -235,71 +448,351 @@ impl TpGxTexHeadManager {
         dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(1)?;
         dds_bytes.write_u32::<byteorder::LittleEndian>(self.tp_gx_tex_head.format.get_alpha_mode())?;
-        dds_bytes.write(&self.resource)?;
+        dds_bytes.write_all(data)?;
 
-        self.dds_bytes = dds_bytes;
-        Ok(())
+        Ok(dds_bytes)
+    }
+
+    fn read_dds(&self) -> Result<image_dds::ddsfile::Dds, std::io::Error> {
+        image_dds::ddsfile::Dds::read(std::io::Cursor::new(&self.dds_bytes)).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    fn decode_mip(&self, dds: &image_dds::ddsfile::Dds, mip: u32) -> Result<image::RgbaImage, std::io::Error> {
+        image_dds::image_from_dds(dds, mip).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    fn decode_hdr_mip(&self, dds: &image_dds::ddsfile::Dds, mip: u32) -> Result<image::Rgba32FImage, std::io::Error> {
+        image_dds::imagef32_from_dds(dds, mip).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
     }
 
-    fn populate_png_bytes(&mut self) -> Result<(), std::io::Error> {
+    fn populate_mip_images(&mut self) -> Result<(), std::io::Error> {
+        self.mip_images.clear();
+        self.hdr_mip_images.clear();
+        self.normal_mip_images.clear();
+
+        let dds = self.read_dds()?;
         for mip in 0..self.tp_gx_tex_head.mip_count {
-            let img = image_dds::image_from_dds(
-                &image_dds::ddsfile::Dds::read(std::io::Cursor::new(self.dds_bytes.clone())).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
-                mip
-            )
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            let img = self.decode_mip(&dds, mip)?;
+
+            if self.tp_gx_tex_head.format.is_normal_map() {
+                self.normal_mip_images.push(normal_map::reconstruct_z(&img));
+            }
 
-            let mut png_bytes = Vec::new();
-            img.write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            self.mip_images.push(img);
 
-            self.png_images.push(png_bytes);
+            if self.tp_gx_tex_head.format.is_hdr() {
+                let img = self.decode_hdr_mip(&dds, mip)?;
+                self.hdr_mip_images.push(img);
+            }
         }
         Ok(())
     }
 
-    fn export_dds(&self) -> Result<(), std::io::Error> {
+    // Mips are only decoded once the texture is previewed, so opening a pack stays cheap
+    fn ensure_mip_images(&mut self) {
+        if self.mip_images_decoded || self.dds_bytes.is_empty() {
+            return;
+        }
+        self.mip_images_decoded = true;
+        if let Err(e) = self.populate_mip_images() {
+            println!("Failed to populate mip images: {}", e);
+            self.mip_images = Vec::new();
+            self.hdr_mip_images = Vec::new();
+            self.normal_mip_images = Vec::new();
+        }
+    }
+
+    // Decodes the smallest mip that still covers `size` pixels, falling back to the base level
+    pub fn thumbnail(&self, size: u32) -> Result<image::RgbaImage, std::io::Error> {
         if self.dds_bytes.is_empty() {
             return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "DDS bytes are empty."));
         }
+        let tp_gx_tex_head = &self.tp_gx_tex_head;
+        let mip = (0..tp_gx_tex_head.mip_count)
+            .rev()
+            .find(|mip| (tp_gx_tex_head.width >> mip).max(tp_gx_tex_head.height >> mip) >= size)
+            .unwrap_or(0);
+
+        let img = self.decode_mip(&self.read_dds()?, mip)?;
+        Ok(match tp_gx_tex_head.format.is_normal_map() {
+            true => normal_map::reconstruct_z(&img),
+            false => img,
+        })
+    }
 
-        let Some(output_path) = rfd::FileDialog::new().set_title(format!("Export {} as DDS", self.path.to_str().unwrap_or_default())).set_file_name(format!("{}.dds", self.path.file_name().unwrap_or_default().to_str().unwrap_or_default())).save_file() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output path not found."));
-        };
+    pub fn width(&self) -> u32 {
+        self.tp_gx_tex_head.width
+    }
 
-        let output_dir = output_path.parent().ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."))?;
+    pub fn height(&self) -> u32 {
+        self.tp_gx_tex_head.height
+    }
 
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir)?;
+    pub fn format_name(&self) -> String {
+        self.tp_gx_tex_head.format.name()
+    }
+
+    fn get_ldr_image(&self, mip: usize) -> Option<&image::RgbaImage> {
+        if self.reconstruct_normal_z && !self.normal_mip_images.is_empty() {
+            self.normal_mip_images.get(mip)
+        } else {
+            self.mip_images.get(mip)
         }
+    }
 
-        let mut output_file = std::fs::File::create(output_path)?;
-        output_file.write_all(&self.dds_bytes)?;
-        output_file.flush()?;
+    // Captures the decoded mips as currently displayed, including normal Z reconstruction
+    fn snapshot(&self) -> TextureSnapshot {
+        TextureSnapshot {
+            label: self.path.to_str().unwrap_or_default().to_string(),
+            width: self.tp_gx_tex_head.width,
+            height: self.tp_gx_tex_head.height,
+            depth: self.tp_gx_tex_head.depth,
+            format: self.tp_gx_tex_head.format.name(),
+            mip_count: self.tp_gx_tex_head.mip_count,
+            size: self.tp_gx_tex_head.size,
+            mips: (0..self.mip_images.len()).filter_map(|mip| self.get_ldr_image(mip).cloned()).collect(),
+        }
+    }
 
-        Ok(())
+    // Whether `straight_alpha` applies to this texture, only premultiplied formats with alpha are converted
+    fn converts_alpha(&self, straight_alpha: bool) -> bool {
+        let format = &self.tp_gx_tex_head.format;
+        straight_alpha && format.has_alpha() && format.alpha_mode() == AlphaMode::Premultiplied
     }
 
-    fn export_png(&self) -> Result<(), std::io::Error> {
-        if self.selected_png_index >= self.png_images.len() {
-            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "PNG bytes are empty."));
+    fn import_image(&mut self) -> Result<(), std::io::Error> {
+        let Some(image_format) = self.tp_gx_tex_head.format.to_image_format() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("Importing into format {:X} is not supported.", self.tp_gx_tex_head.format.to_u32())));
+        };
+        if self.tp_gx_tex_head.depth > 1 {
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Importing volume textures is not supported."));
         }
 
-        let Some(output_path) = rfd::FileDialog::new().set_title(format!("Export {} as PNG", self.path.to_str().unwrap_or_default())).set_file_name(format!("{}.png", self.path.file_name().unwrap_or_default().to_str().unwrap_or_default())).save_file() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output path not found."));
+        let Some(input_path) = rfd::FileDialog::new().set_title(format!("Import image into {}", self.path.to_str().unwrap_or_default())).add_filter("Image", &["png", "tga", "dds", "exr", "hdr", "tif", "tiff", "bmp", "jpg", "jpeg"]).pick_file() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Input path not found."));
         };
 
+        let input_image = image::open(input_path).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if input_image.width() != self.tp_gx_tex_head.width || input_image.height() != self.tp_gx_tex_head.height {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Image is {}×{}, expected {}×{}.", input_image.width(), input_image.height(), self.tp_gx_tex_head.width, self.tp_gx_tex_head.height)));
+        }
+
+        let mipmaps = image_dds::Mipmaps::GeneratedExact(self.tp_gx_tex_head.mip_count);
+        let dds = if self.tp_gx_tex_head.format.is_hdr() {
+            image_dds::dds_from_imagef32(&input_image.to_rgba32f(), image_format, image_dds::Quality::Normal, mipmaps)
+        } else if self.tp_gx_tex_head.format.is_normal_map() {
+            image_dds::dds_from_image(&normal_map::drop_z(&input_image.to_rgba8()), image_format, image_dds::Quality::Normal, mipmaps)
+        } else if self.converts_alpha(self.convert_straight_alpha) {
+            image_dds::dds_from_image(&alpha::premultiply(&input_image.to_rgba8()), image_format, image_dds::Quality::Normal, mipmaps)
+        } else {
+            image_dds::dds_from_image(&input_image.to_rgba8(), image_format, image_dds::Quality::Normal, mipmaps)
+        }
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        if dds.data.len() != self.resource.len() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Encoded size {} does not match resource size {}.", dds.data.len(), self.resource.len())));
+        }
+
+        self.set_resource(dds.data);
+        Ok(())
+    }
+
+    fn get_export_formats(&self) -> Vec<ExportFormat> {
+        ExportFormat::ALL.into_iter().filter(|format| match format {
+            ExportFormat::Dds => !self.dds_bytes.is_empty(),
+            ExportFormat::DdsLegacy => self.tp_gx_tex_head.format.to_legacy_pixel_format().is_some(),
+            ExportFormat::Ktx2 => self.tp_gx_tex_head.format.to_vk_format().is_some(),
+            ExportFormat::Png | ExportFormat::Tga => !self.dds_bytes.is_empty(),
+            ExportFormat::Exr | ExportFormat::Hdr => !self.dds_bytes.is_empty() && self.tp_gx_tex_head.format.is_hdr(),
+        }).collect()
+    }
+
+    // Image formats encode the decoded `mip`, containers hold the first `mip_count` levels
+    fn get_export_bytes(&self, format: ExportFormat, mip: usize, mip_count: u32, straight_alpha: bool) -> Result<Vec<u8>, std::io::Error> {
+        let tp_gx_tex_head = &self.tp_gx_tex_head;
+        let mip_count = mip_count.clamp(1, tp_gx_tex_head.mip_count.max(1));
+        let container_data = self.resource.get(..tp_gx_tex_head.get_mip_range(mip_count - 1).end).ok_or(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Resource is smaller than its mips."))?;
+        let mut bytes = Vec::new();
+        match format {
+            ExportFormat::Dds => {
+                if self.dds_bytes.is_empty() {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "DDS bytes are empty."));
+                }
+                bytes = match mip_count == tp_gx_tex_head.surface_count {
+                    true => self.dds_bytes.clone(),
+                    false => self.build_dds_bytes(mip_count)?,
+                };
+            },
+            ExportFormat::DdsLegacy => {
+                let Some(pixel_format) = tp_gx_tex_head.format.to_legacy_pixel_format() else {
+                    return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("Format {:X} has no legacy DDS equivalent.", tp_gx_tex_head.format.to_u32())));
+                };
+                let (block_dimension, bytes_per_block) = tp_gx_tex_head.format.get_block_info();
+                let pitch_or_linear_size = match block_dimension {
+                    1 => tp_gx_tex_head.width * bytes_per_block,
+                    _ => tp_gx_tex_head.get_mip_range(0).len() as u32,
+                };
+                bytes = dds::write_legacy_dds(tp_gx_tex_head.width, tp_gx_tex_head.height, tp_gx_tex_head.depth, mip_count, pitch_or_linear_size, pixel_format, container_data)?;
+            },
+            ExportFormat::Ktx2 => {
+                let Some(vk_format) = tp_gx_tex_head.format.to_vk_format() else {
+                    return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("Format {:X} has no KTX2 equivalent.", tp_gx_tex_head.format.to_u32())));
+                };
+                let levels = (0..mip_count)
+                    .map(|mip| self.resource.get(tp_gx_tex_head.get_mip_range(mip)).ok_or(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("Mip {} is out of bounds.", mip))))
+                    .collect::<Result<Vec<_>, _>>()?;
+                bytes = ktx2::write_ktx2(vk_format, tp_gx_tex_head.width, tp_gx_tex_head.height, tp_gx_tex_head.depth, tp_gx_tex_head.format.get_alpha_mode() == 2, &levels)?;
+            },
+            ExportFormat::Png | ExportFormat::Tga => {
+                // Batch exports never preview the texture, so decode the mip on demand
+                let image = match self.get_ldr_image(mip) {
+                    Some(image) => std::borrow::Cow::Borrowed(image),
+                    None => {
+                        let img = self.decode_mip(&self.read_dds()?, mip as u32)?;
+                        std::borrow::Cow::Owned(match self.reconstruct_normal_z && tp_gx_tex_head.format.is_normal_map() {
+                            true => normal_map::reconstruct_z(&img),
+                            false => img,
+                        })
+                    }
+                };
+                let image = match self.converts_alpha(straight_alpha) {
+                    true => std::borrow::Cow::Owned(alpha::unpremultiply(&image)),
+                    false => image,
+                };
+                let image_format = if format == ExportFormat::Png { image::ImageFormat::Png } else { image::ImageFormat::Tga };
+                image.write_to(&mut std::io::Cursor::new(&mut bytes), image_format).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            },
+            ExportFormat::Exr | ExportFormat::Hdr => {
+                let hdr_image = match self.hdr_mip_images.get(mip) {
+                    Some(hdr_image) => hdr_image.clone(),
+                    None => self.decode_hdr_mip(&self.read_dds()?, mip as u32)?,
+                };
+                // Radiance HDR has no alpha channel
+                let image = match format {
+                    ExportFormat::Hdr => image::DynamicImage::ImageRgb32F(image::DynamicImage::ImageRgba32F(hdr_image).to_rgb32f()),
+                    _ => image::DynamicImage::ImageRgba32F(hdr_image),
+                };
+                let image_format = if format == ExportFormat::Exr { image::ImageFormat::OpenExr } else { image::ImageFormat::Hdr };
+                image.write_to(&mut std::io::Cursor::new(&mut bytes), image_format).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            }
+        }
+        Ok(bytes)
+    }
+
+    // `straight_alpha` unpremultiplies PNG and TGA exports of premultiplied textures
+    pub fn write_export(&self, output_path: &std::path::Path, format: ExportFormat, mips: ExportMips, straight_alpha: bool) -> Result<(), std::io::Error> {
         let output_dir = output_path.parent().ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, "Output folder not found."))?;
 
         if !output_dir.exists() {
             std::fs::create_dir_all(output_dir)?;
         }
 
-        let mut output_file = std::fs::File::create(output_path)?;
-        output_file.write_all(&self.png_images[self.selected_png_index])?;
-        output_file.flush()?;
+        let mut outputs = Vec::new();
+        match (format.is_image(), mips) {
+            (true, ExportMips::All) => {
+                let stem = output_path.file_stem().unwrap_or_default().to_str().unwrap_or_default();
+                for mip in 0..self.tp_gx_tex_head.mip_count as usize {
+                    outputs.push((output_dir.join(format!("{}.mip{}.{}", stem, mip, format.extension())), mip, 1));
+                }
+            },
+            (true, ExportMips::Single(mip)) => outputs.push((output_path.to_path_buf(), mip, 1)),
+            (false, ExportMips::All) => outputs.push((output_path.to_path_buf(), 0, self.tp_gx_tex_head.mip_count)),
+            (false, ExportMips::Single(_)) => outputs.push((output_path.to_path_buf(), 0, 1)),
+        }
+
+        for (output_path, mip, mip_count) in outputs {
+            let bytes = self.get_export_bytes(format, mip, mip_count, straight_alpha)?;
+            let mut output_file = std::fs::File::create(output_path)?;
+            output_file.write_all(&bytes)?;
+            output_file.flush()?;
+        }
 
         Ok(())
     }
+
+    fn export(&self) -> Result<(), std::io::Error> {
+        let format = self.export_format;
+        let file_name = self.path.file_name().unwrap_or_default().to_str().unwrap_or_default();
+        let Some(output_path) = rfd::FileDialog::new().set_title(format!("Export {} as {}", self.path.to_str().unwrap_or_default(), format.name())).set_file_name(format!("{}.{}", file_name, format.extension())).save_file() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output path not found."));
+        };
+
+        let mips = match format.is_image() && !self.export_all_mips {
+            true => ExportMips::Single(self.selected_mip_index),
+            false => ExportMips::All,
+        };
+        self.write_export(&output_path, format, mips, self.convert_straight_alpha)
+    }
+
+    fn paint_data_preview(&mut self, ui: &mut eframe::egui::Ui, channels: usize, component: ComponentType, toasts: &mut egui_notify::Toasts) {
+        let mip_count = self.tp_gx_tex_head.mip_count.max(1) as usize;
+        self.selected_mip_index = self.selected_mip_index.min(mip_count - 1);
+        ui.horizontal(|ui| {
+            ui.label("Mip:");
+            ui.add(egui::Slider::new(&mut self.selected_mip_index, 0..=(mip_count - 1)).show_value(true));
+        });
+
+        // Volume slices are stacked vertically
+        let mip = self.selected_mip_index as u32;
+        let width = (self.tp_gx_tex_head.width >> mip).max(1);
+        let height = (self.tp_gx_tex_head.height >> mip).max(1) * (self.tp_gx_tex_head.depth >> mip).max(1);
+        let Some(data) = self.resource.get(self.tp_gx_tex_head.get_mip_range(mip)) else {
+            ui.label("Mip data is out of bounds.");
+            return;
+        };
+        self.data_viewer.show(ui, DataTexels { data, width, height, channels, component }, self.selected_mip_index, toasts);
+    }
+
+    fn paint_export_dialog(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
+        let mut open = self.export_dialog_open;
+        let formats = self.get_export_formats();
+        if !formats.contains(&self.export_format) && let Some(format) = formats.first() {
+            self.export_format = *format;
+        }
+
+        egui::Window::new(format!("{} Export {}", egui_phosphor::regular::EXPORT, self.path.file_name().unwrap_or_default().to_str().unwrap_or_default()))
+            .id(egui::Id::new(&self.path).with("export_dialog"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                egui::ComboBox::from_label("Format")
+                    .selected_text(self.export_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in formats.iter() {
+                            ui.selectable_value(&mut self.export_format, *format, format.name());
+                        }
+                    });
+
+                if self.export_format.is_image() {
+                    ui.radio_value(&mut self.export_all_mips, false, format!("Mip {}", self.selected_mip_index));
+                    ui.radio_value(&mut self.export_all_mips, true, "All mips");
+                    if !self.normal_mip_images.is_empty() && !self.export_format.is_hdr() && ui.checkbox(&mut self.reconstruct_normal_z, "Reconstruct normal Z").changed() {
+                        self.viewer.invalidate();
+                    }
+                    if self.tp_gx_tex_head.format.has_alpha() && self.tp_gx_tex_head.format.alpha_mode() == AlphaMode::Premultiplied && !self.export_format.is_hdr() {
+                        ui.checkbox(&mut self.convert_straight_alpha, "Convert to straight alpha");
+                    }
+                } else {
+                    ui.label("Contains all mips.");
+                }
+
+                ui.separator();
+                if ui.button("Export…").clicked() {
+                    match self.export() {
+                        Ok(_) => {
+                            toasts.success(format!("{} exported successfully.", self.export_format.name())).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            self.export_dialog_open = false;
+                        },
+                        Err(e) => {
+                            toasts.error(format!("Failed to export {}: {}", self.export_format.name(), e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                        }
+                    }
+                }
+            });
+
+        self.export_dialog_open &= open;
+    }
 }
 
 impl Manager for TpGxTexHeadManager {
@@ -307,6 +800,10 @@ impl Manager for TpGxTexHeadManager {
         &self.path
     }
 
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        Ok(Some(DataExport::new(&self.tp_gx_tex_head)?.with_table("surfaces", self.tp_gx_tex_head.surfaces.iter())?))
+    }
+
     fn paint(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         egui::Frame::window(&ui.style()).show(ui, |ui| {
             egui::CollapsingHeader::new(egui::RichText::new(self.title()).heading())
@@ -316,7 +813,8 @@ impl Manager for TpGxTexHeadManager {
                     ui.label(format!("Height: {}", self.tp_gx_tex_head.height));
                     ui.label(format!("Depth: {}", self.tp_gx_tex_head.depth));
                     ui.label(format!("Size: {}", self.tp_gx_tex_head.size));
-                    ui.label(format!("Format: {:X}", self.tp_gx_tex_head.format.to_u32()));
+                    ui.label(format!("Format: {} ({:X})", self.tp_gx_tex_head.format.name(), self.tp_gx_tex_head.format.to_u32()));
+                    ui.label(format!("Alpha Mode: {}", self.tp_gx_tex_head.format.alpha_mode().name()));
                     ui.label(format!("Mip Count: {}", self.tp_gx_tex_head.mip_count));
                     ui.label(format!("Surface Count: {}", self.tp_gx_tex_head.surface_count));
                 });
@@ -342,55 +840,89 @@ impl Resource for TpGxTexHeadManager {
                 self.dds_bytes = Vec::new();
             }
         }
-        if !self.dds_bytes.is_empty() {
-            match self.populate_png_bytes() {
-                Ok(_) => {},
-                Err(e) => {
-                    println!("Failed to populate png bytes: {}", e);
-                    self.png_images = Vec::new();
-                }
-            }
-        }
+        self.mip_images = Vec::new();
+        self.hdr_mip_images = Vec::new();
+        self.normal_mip_images = Vec::new();
+        self.mip_images_decoded = false;
+        self.viewer.invalidate();
+        self.data_viewer.invalidate();
+    }
+
+    fn as_texture(&self) -> Option<&TpGxTexHeadManager> {
+        Some(self)
     }
     
     fn resource_preview(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
-        ui.horizontal(|ui| {
-            ui.label("Mip:");
-            ui.add(egui::Slider::new(&mut self.selected_png_index, 0..=(self.png_images.len() - 1)).show_value(true));
-        });
-        if !self.png_images.is_empty() && self.selected_png_index < self.png_images.len() {
-            let uri = Cow::from(format!("bytes://{}.{}.png", self.path.to_str().unwrap_or_default(), self.selected_png_index));
-            ui.add(egui::Image::new(egui::ImageSource::Bytes { uri, bytes: self.png_images[self.selected_png_index].clone().into() }).texture_options(egui::TextureOptions::NEAREST).show_loading_spinner(true).maintain_aspect_ratio(true).fit_to_exact_size(egui::Vec2::new(512.0, 512.0)));
-            // Export buttons
+        if let Some((channels, component)) = self.tp_gx_tex_head.format.get_data_layout() {
             ui.horizontal(|ui| {
-                if ui.button("Export DDS").clicked() {
-                    match self.export_dds() {
+                ui.selectable_value(&mut self.show_data, false, format!("{} Image", egui_phosphor::regular::IMAGE));
+                ui.selectable_value(&mut self.show_data, true, format!("{} Data", egui_phosphor::regular::TABLE));
+            });
+            if self.show_data {
+                self.paint_data_preview(ui, channels, component, toasts);
+                return;
+            }
+        }
+
+        self.ensure_mip_images();
+        if !self.mip_images.is_empty() && self.selected_mip_index < self.mip_images.len() {
+            ui.horizontal(|ui| {
+                ui.label("Mip:");
+                ui.add(egui::Slider::new(&mut self.selected_mip_index, 0..=(self.mip_images.len() - 1)).show_value(true));
+            });
+
+            let (block_dimension, bytes_per_block) = self.tp_gx_tex_head.format.get_block_info();
+            let raw = self.resource.get(self.tp_gx_tex_head.get_mip_range(self.selected_mip_index as u32)).map(|data| RawTexels { data, block_dimension, bytes_per_block });
+            if !self.normal_mip_images.is_empty() && ui.checkbox(&mut self.reconstruct_normal_z, "Reconstruct normal Z").changed() {
+                self.viewer.invalidate();
+            }
+
+            let image = match self.hdr_mip_images.get(self.selected_mip_index) {
+                Some(hdr_image) => ViewerImage::Hdr(hdr_image),
+                None if self.reconstruct_normal_z && self.selected_mip_index < self.normal_mip_images.len() => ViewerImage::Ldr(&self.normal_mip_images[self.selected_mip_index]),
+                None => ViewerImage::Ldr(&self.mip_images[self.selected_mip_index]),
+            };
+            self.viewer.show(ui, image, self.selected_mip_index, raw);
+
+            ui.horizontal(|ui| {
+                if ui.button("Export…").clicked() {
+                    self.export_dialog_open = true;
+                }
+
+                let import_button = ui.button("Import image…");
+                let import_button = match self.converts_alpha(self.convert_straight_alpha) {
+                    true => import_button.on_hover_text("Straight alpha is premultiplied to match the texture. Toggle \"Convert to straight alpha\" in the export dialog to import as-is."),
+                    false => import_button,
+                };
+                if import_button.clicked() {
+                    match self.import_image() {
                         Ok(_) => {
-                            toasts.success(format!("DDS exported successfully.")).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            toasts.success("Image imported successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
                         },
                         Err(e) => {
-                            toasts.error(format!("Failed to export DDS: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            toasts.error(format!("Failed to import image: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
                         }
                     }
                 }
 
-                if ui.button("Export PNG").clicked() {
-                    match self.export_png() {
-                        Ok(_) => {
-                            toasts.success(format!("PNG exported successfully.")).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                        },
-                        Err(e) => {
-                            toasts.error(format!("Failed to export PNG: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
-                        }
+                ui.separator();
+                for slot in [CompareSlot::A, CompareSlot::B] {
+                    if ui.button(format!("Compare as {}", slot.name())).clicked() {
+                        compare::set_slot(ui.ctx(), slot, self.snapshot());
                     }
                 }
             });
+
+            if self.export_dialog_open {
+                self.paint_export_dialog(ui, toasts);
+            }
         }
     }
 }
 
 impl ResourceManager for TpGxTexHeadManager {}
 
+#[derive(serde::Serialize)]
 struct Surface {
     offset: u32,
     unknown_0: u32,
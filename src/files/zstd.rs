@@ -2,7 +2,7 @@ use std::{io::{Read, Seek}, path::PathBuf};
 
 use byteorder::ReadBytesExt;
 
-use crate::{files::bxon::BxonManager, traits::*};
+use crate::{data_export::DataExport, files::bxon::BxonManager, traits::*};
 
 struct Zstd {}
 
@@ -62,4 +62,12 @@ impl Manager for ZstdManager {
     fn paint_floating(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
         self.contents.paint_floating(ui, toasts);
     }
+
+    fn take_pending_opens(&mut self) -> Vec<PendingOpen> {
+        self.contents.take_pending_opens()
+    }
+
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        self.contents.data_export()
+    }
 }
\ No newline at end of file
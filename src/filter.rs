@@ -0,0 +1,299 @@
+use regex::Regex;
+
+// What a filter can look at. Fields that a table does not have are left as None and never match a predicate on them.
+#[derive(Default)]
+pub struct FilterEntry<'a> {
+    pub path: &'a str,
+    pub hash: u32,
+    pub archive: Option<&'a str>,
+    pub size: Option<u64>,
+    pub compressed_size: Option<u64>,
+    pub compressed: Option<bool>,
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn compare(&self, value: u64, target: u64) -> bool {
+        match self {
+            Comparison::Less => value < target,
+            Comparison::LessOrEqual => value <= target,
+            Comparison::Equal => value == target,
+            Comparison::GreaterOrEqual => value >= target,
+            Comparison::Greater => value > target,
+        }
+    }
+}
+
+enum Matcher {
+    Contains(String),
+    Pattern(Regex),
+}
+
+impl Matcher {
+    // "/…/" is a regex, text with '*' or '?' a glob and anything else a substring, all case-insensitive
+    fn parse(text: &str) -> Result<Self, String> {
+        if text.len() > 1 && text.starts_with('/') && text.ends_with('/') {
+            let pattern = &text[1..text.len() - 1];
+            return Regex::new(&format!("(?i){}", pattern)).map(Matcher::Pattern).map_err(|e| format!("Invalid regex \"{}\": {}", pattern, e));
+        }
+        if text.contains(['*', '?']) {
+            return Regex::new(&glob_to_regex(text)).map(Matcher::Pattern).map_err(|e| format!("Invalid glob \"{}\": {}", text, e));
+        }
+        Ok(Matcher::Contains(text.to_lowercase()))
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Matcher::Contains(text) => value.to_lowercase().contains(text),
+            Matcher::Pattern(regex) => regex.is_match(value),
+        }
+    }
+}
+
+// '*' matches any run of characters including '/', '?' a single character
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("(?i)^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+enum Predicate {
+    Path(Matcher),
+    Archive(Matcher),
+    Extension(String),
+    Hash(u32),
+    Size(Comparison, u64),
+    CompressedSize(Comparison, u64),
+    Compressed(bool),
+}
+
+impl Predicate {
+    fn matches(&self, entry: &FilterEntry) -> bool {
+        match self {
+            Predicate::Path(matcher) => matcher.matches(entry.path),
+            Predicate::Archive(matcher) => entry.archive.is_some_and(|archive| matcher.matches(archive)),
+            Predicate::Extension(extension) => entry.path.to_lowercase().ends_with(extension),
+            Predicate::Hash(hash) => entry.hash == *hash,
+            Predicate::Size(comparison, size) => entry.size.is_some_and(|value| comparison.compare(value, *size)),
+            Predicate::CompressedSize(comparison, size) => entry.compressed_size.is_some_and(|value| comparison.compare(value, *size)),
+            Predicate::Compressed(compressed) => entry.compressed == Some(*compressed),
+        }
+    }
+}
+
+struct Term {
+    negated: bool,
+    predicate: Predicate,
+}
+
+// Whitespace separated terms that must all match, e.g. `archive:data003 size>1MB compressed:false ext:.pack`.
+// A leading '-' negates a term and double quotes keep spaces together.
+#[derive(Default)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number = number.parse::<f64>().map_err(|_| format!("Invalid size \"{}\"", text))?;
+    let multiplier = match unit.to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("Unknown size unit \"{}\"", unit)),
+    };
+    Ok((number * multiplier) as u64)
+}
+
+fn parse_bool(text: &str) -> Result<bool, String> {
+    match text.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("Expected true or false, found \"{}\"", text)),
+    }
+}
+
+// Splits on whitespace outside of double quotes
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            },
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn parse_predicate(token: &str) -> Result<Predicate, String> {
+    for (field, predicate) in [("size", Predicate::Size as fn(Comparison, u64) -> Predicate), ("csize", Predicate::CompressedSize)] {
+        let Some(rest) = token.strip_prefix(field) else {
+            continue;
+        };
+        let (comparison, value) = if let Some(value) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (Comparison::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (Comparison::Less, value)
+        } else if let Some(value) = rest.strip_prefix('=').or_else(|| rest.strip_prefix(':')) {
+            (Comparison::Equal, value)
+        } else {
+            continue;
+        };
+        return Ok(predicate(comparison, parse_size(value)?));
+    }
+
+    let Some((field, value)) = token.split_once(':') else {
+        return Ok(Predicate::Path(Matcher::parse(token)?));
+    };
+    match field.to_lowercase().as_str() {
+        "path" | "name" => Ok(Predicate::Path(Matcher::parse(value)?)),
+        "archive" => Ok(Predicate::Archive(Matcher::parse(value)?)),
+        "ext" => {
+            let extension = value.to_lowercase();
+            Ok(Predicate::Extension(match extension.starts_with('.') {
+                true => extension,
+                false => format!(".{}", extension),
+            }))
+        },
+        "hash" => crate::hash::parse_hash(value).map(Predicate::Hash).ok_or_else(|| format!("Invalid hash \"{}\"", value)),
+        "compressed" => Ok(Predicate::Compressed(parse_bool(value)?)),
+        // Unknown fields are treated as text so paths containing ':' still work
+        _ => Ok(Predicate::Path(Matcher::parse(token)?)),
+    }
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for token in tokenize(text) {
+            let (negated, token) = match token.strip_prefix('-') {
+                Some(token) if !token.is_empty() => (true, token),
+                _ => (false, token.as_str()),
+            };
+            terms.push(Term { negated, predicate: parse_predicate(token)? });
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &FilterEntry) -> bool {
+        self.terms.iter().all(|term| term.predicate.matches(entry) != term.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> FilterEntry<'_> {
+        FilterEntry { path, ..Default::default() }
+    }
+
+    fn matches(filter: &str, entry: &FilterEntry) -> bool {
+        Filter::parse(filter).unwrap().matches(entry)
+    }
+
+    #[test]
+    fn plain_text_is_case_insensitive_substring() {
+        assert!(matches("Title", &entry("core/title.pack")));
+        assert!(!matches("menu", &entry("core/title.pack")));
+    }
+
+    #[test]
+    fn glob_matches_whole_path() {
+        assert!(matches("*.pack", &entry("core/title.pack")));
+        assert!(matches("core/t?tle.*", &entry("core/title.pack")));
+        assert!(!matches("*.pac", &entry("core/title.pack")));
+    }
+
+    #[test]
+    fn slashes_make_a_regex() {
+        assert!(matches("/^core/.+\\.PACK$/", &entry("core/title.pack")));
+        assert!(!matches("/^title/", &entry("core/title.pack")));
+        assert!(Filter::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn size_units_and_comparisons() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("2kb").unwrap(), 2048);
+        assert_eq!(parse_size("1.5MB").unwrap(), 1536 * 1024);
+        assert_eq!(parse_size("1GiB").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("1tb").is_err());
+
+        let file = FilterEntry { path: "a", size: Some(2 * 1024 * 1024), compressed_size: Some(1000), ..Default::default() };
+        assert!(matches("size>1MB", &file));
+        assert!(matches("size>=2mb", &file));
+        assert!(!matches("size<2mb", &file));
+        assert!(matches("size:2mb", &file));
+        assert!(matches("csize<=1000", &file));
+        assert!(!matches("csize>1000", &file));
+        // Missing fields never match
+        assert!(!matches("size>0", &entry("a")));
+    }
+
+    #[test]
+    fn field_prefixes() {
+        let file = FilterEntry { path: "core/title.pack", hash: 0x1234ABCD, archive: Some("data003.arc"), compressed: Some(false), ..Default::default() };
+        assert!(matches("archive:data003", &file));
+        assert!(!matches("archive:data004", &file));
+        assert!(matches("ext:pack", &file));
+        assert!(matches("ext:.PACK", &file));
+        assert!(matches("name:title", &file));
+        assert!(matches("path:core/*", &file));
+        assert!(matches("hash:0x1234abcd", &file));
+        assert!(!matches("hash:1234abce", &file));
+        assert!(matches("compressed:false", &file));
+        assert!(!matches("compressed:yes", &file));
+        assert!(Filter::parse("compressed:maybe").is_err());
+        // Unknown fields fall back to matching the whole token as text
+        assert!(matches("c:/x", &entry("c:/x/y")));
+    }
+
+    #[test]
+    fn negation_and_quotes() {
+        let file = entry("core/my title.pack");
+        assert!(matches("-ext:bxon", &file));
+        assert!(!matches("-ext:pack", &file));
+        assert!(matches("\"my title\" core", &file));
+        assert!(!matches("\"my title\" -core", &file));
+        // A lone '-' is plain text
+        assert!(!matches("-", &file));
+        assert!(Filter::parse("   ").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+// Paths are hashed as CRC-32 (IEEE) over their lowercased UTF-8 bytes.
+// Not yet confirmed against game data, hash_path_matches_info_arc checks it against a real index.
+pub fn hash_path(path: &str) -> u32 {
+    crc32fast::hash(path.to_lowercase().as_bytes())
+}
+
+// Entries without a name have nothing to verify against
+pub fn verify(path: &str, hash: u32) -> bool {
+    path.is_empty() || hash_path(path) == hash
+}
+
+// Accepts "1234ABCD" and "0x1234ABCD"
+pub fn parse_hash(query: &str) -> Option<u32> {
+    let query = query.trim();
+    let digits = query.strip_prefix("0x").or_else(|| query.strip_prefix("0X")).unwrap_or(query);
+    if digits.len() != 8 {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+// True when `query` names this entry, either by its path or by its hash
+pub fn matches_query(query: &str, name: &str, hash: u32) -> bool {
+    let query = query.trim();
+    if query.is_empty() {
+        return false;
+    }
+    if parse_hash(query) == Some(hash) {
+        return true;
+    }
+    name.eq_ignore_ascii_case(query) || hash_path(query) == hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only pins the algorithm, a (name, hash) pair from a real info.arc still has to be added
+    #[test]
+    fn hash_path_is_crc32_ieee() {
+        // Standard CRC-32 check value
+        assert_eq!(hash_path("123456789"), 0xCBF43926);
+        assert_eq!(hash_path(""), 0);
+    }
+
+    #[test]
+    fn hash_path_ignores_case() {
+        assert_eq!(hash_path("Core/Core.pack"), hash_path("core/core.pack"));
+        assert_eq!(hash_path("CORE/CORE.PACK"), crc32fast::hash(b"core/core.pack"));
+    }
+
+    #[test]
+    fn parse_hash_accepts_prefix() {
+        assert_eq!(parse_hash("0x1234ABCD"), Some(0x1234ABCD));
+        assert_eq!(parse_hash("1234abcd"), Some(0x1234ABCD));
+        assert_eq!(parse_hash("1234ABC"), None);
+    }
+
+    // Checks every named entry of a real index, e.g.
+    // REPLICANT_INFO_ARC=".../data/info.arc" cargo test -- --ignored
+    #[test]
+    #[ignore = "needs an info.arc from a game install"]
+    fn hash_path_matches_info_arc() {
+        let path = std::env::var("REPLICANT_INFO_ARC").expect("REPLICANT_INFO_ARC is not set");
+        let (document, _) = crate::index_document::IndexDocument::export(&std::fs::read(path).unwrap()).unwrap();
+        let named = document.files.iter().filter(|file| !file.name.is_empty()).collect::<Vec<_>>();
+        assert!(!named.is_empty());
+        let mismatches = named.iter().filter(|file| !verify(&file.name, file.hash)).map(|file| file.name.as_str()).collect::<Vec<_>>();
+        assert!(mismatches.is_empty(), "{} of {} names do not match their hash, e.g. {:?}", mismatches.len(), named.len(), &mismatches[..mismatches.len().min(5)]);
+    }
+}
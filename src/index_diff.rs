@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::vfs::{Metadata, Vfs};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    // Uncompressed size differs
+    Resized,
+    // Same size but the compressed data differs, so the contents changed
+    Modified,
+    // Same sizes at a different archive or offset, `verify_moved` can confirm the data is the same
+    Moved,
+}
+
+// Whether the contents of an entry were read back from both installs
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verification {
+    // Only the index metadata was compared
+    NotChecked,
+    // Both versions were read, a moved entry holds the same data
+    Verified,
+    // An archive could not be read, so the data may differ
+    Unverified,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 5] = [ChangeKind::Added, ChangeKind::Removed, ChangeKind::Resized, ChangeKind::Modified, ChangeKind::Moved];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Resized => "Resized",
+            ChangeKind::Modified => "Modified",
+            ChangeKind::Moved => "Moved",
+        }
+    }
+}
+
+impl Verification {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verification::NotChecked => "Not checked",
+            Verification::Verified => "Verified",
+            Verification::Unverified => "Unverified",
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct IndexChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    pub hash: u32,
+    pub old: Option<Metadata>,
+    pub new: Option<Metadata>,
+    pub verification: Verification,
+}
+
+impl IndexChange {
+    // The newer version of the entry, or the old one when it was removed
+    pub fn metadata(&self) -> &Metadata {
+        self.new.as_ref().or(self.old.as_ref()).unwrap()
+    }
+}
+
+// An entry left out of the comparison because an earlier entry in the same index has its name hash
+#[derive(Clone, serde::Serialize)]
+pub struct DuplicateHash {
+    pub index: &'static str,
+    pub metadata: Metadata,
+}
+
+// Entries are matched by their name hash, so files whose names are not known yet are compared too
+#[derive(Default, serde::Serialize)]
+pub struct IndexDiff {
+    pub changes: Vec<IndexChange>,
+    pub unchanged: usize,
+    pub duplicates: Vec<DuplicateHash>,
+}
+
+// The most significant difference between two versions of an entry, None when they are the same
+fn classify(old: &Metadata, new: &Metadata) -> Option<ChangeKind> {
+    if old.size != new.size {
+        Some(ChangeKind::Resized)
+    } else if old.compressed_size != new.compressed_size || old.is_compressed != new.is_compressed {
+        Some(ChangeKind::Modified)
+    } else if old.archive != new.archive || old.offset != new.offset {
+        Some(ChangeKind::Moved)
+    } else {
+        None
+    }
+}
+
+// None when the entry cannot be read, e.g. because its archive is not next to the index
+fn content_hash(vfs: &Vfs, metadata: &Metadata) -> Option<u64> {
+    let data = vfs.read(&metadata.path).ok()?;
+    let mut hasher = std::hash::DefaultHasher::new();
+    data.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn archive_position(metadata: &Option<Metadata>) -> (Option<&str>, Option<u64>) {
+    (metadata.as_ref().map(|metadata| metadata.archive.as_str()), metadata.as_ref().map(|metadata| metadata.offset))
+}
+
+// Keeps the first entry for each hash, like Vfs lookups do, and reports the rest
+fn entries_by_hash(vfs: &Vfs, index: &'static str, duplicates: &mut Vec<DuplicateHash>) -> HashMap<u32, Metadata> {
+    let mut entries = HashMap::new();
+    for metadata in vfs.entries() {
+        match entries.entry(metadata.hash) {
+            std::collections::hash_map::Entry::Occupied(_) => duplicates.push(DuplicateHash { index, metadata }),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(metadata);
+            },
+        }
+    }
+    entries
+}
+
+impl IndexDiff {
+    // Only compares the indexes, no archive is read
+    pub fn compare(old: &Vfs, new: &Vfs) -> Self {
+        let mut diff = IndexDiff::default();
+        let old_entries = entries_by_hash(old, "old", &mut diff.duplicates);
+        let new_entries = entries_by_hash(new, "new", &mut diff.duplicates);
+
+        for (hash, new_metadata) in new_entries.iter() {
+            let kind = match old_entries.get(hash) {
+                Some(old_metadata) => classify(old_metadata, new_metadata),
+                None => Some(ChangeKind::Added),
+            };
+            match kind {
+                Some(kind) => diff.changes.push(IndexChange {
+                    kind,
+                    path: new_metadata.path.clone(),
+                    hash: *hash,
+                    old: old_entries.get(hash).cloned(),
+                    new: Some(new_metadata.clone()),
+                    verification: Verification::NotChecked,
+                }),
+                None => diff.unchanged += 1,
+            }
+        }
+        for (hash, old_metadata) in old_entries.iter().filter(|(hash, _)| !new_entries.contains_key(hash)) {
+            diff.changes.push(IndexChange {
+                kind: ChangeKind::Removed,
+                path: old_metadata.path.clone(),
+                hash: *hash,
+                old: Some(old_metadata.clone()),
+                new: None,
+                verification: Verification::NotChecked,
+            });
+        }
+
+        diff.changes.sort_by(|a, b| a.path.cmp(&b.path));
+        diff.duplicates.sort_by(|a, b| (a.index, &a.metadata.path).cmp(&(b.index, &b.metadata.path)));
+        diff
+    }
+
+    // Reads every moved entry back from both installs and reports it as Modified when the data differs.
+    // Each side is read in archive and offset order so every archive is decompressed once.
+    pub fn verify_moved(&mut self, old: &Vfs, new: &Vfs) {
+        let mut moved = (0..self.changes.len()).filter(|index| self.changes[*index].kind == ChangeKind::Moved).collect::<Vec<_>>();
+
+        moved.sort_by(|a, b| archive_position(&self.changes[*a].old).cmp(&archive_position(&self.changes[*b].old)));
+        let old_hashes = moved.iter().map(|index| (*index, self.changes[*index].old.as_ref().and_then(|metadata| content_hash(old, metadata)))).collect::<HashMap<_, _>>();
+
+        moved.sort_by(|a, b| archive_position(&self.changes[*a].new).cmp(&archive_position(&self.changes[*b].new)));
+        for index in moved {
+            let change = &mut self.changes[index];
+            let new_hash = change.new.as_ref().and_then(|metadata| content_hash(new, metadata));
+            match (old_hashes[&index], new_hash) {
+                (Some(old_hash), Some(new_hash)) => {
+                    change.verification = Verification::Verified;
+                    if old_hash != new_hash {
+                        change.kind = ChangeKind::Modified;
+                    }
+                },
+                _ => change.verification = Verification::Unverified,
+            }
+        }
+    }
+
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|change| change.kind == kind).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let counts = ChangeKind::ALL.iter().map(|kind| format!("{} {}", self.count(*kind), kind.name().to_lowercase())).collect::<Vec<_>>();
+        let mut summary = format!("{}, {} unchanged", counts.join(", "), self.unchanged);
+        let unverified = self.changes.iter().filter(|change| change.verification == Verification::Unverified).count();
+        if unverified > 0 {
+            summary.push_str(&format!(", {} moved entries could not be verified", unverified));
+        }
+        if !self.duplicates.is_empty() {
+            summary.push_str(&format!(", {} duplicate name hashes skipped", self.duplicates.len()));
+        }
+        summary
+    }
+}
+
+pub fn to_json(changes: &[&IndexChange]) -> Result<String, std::io::Error> {
+    serde_json::to_string_pretty(changes).map_err(std::io::Error::other)
+}
+
+pub fn to_csv(changes: &[&IndexChange]) -> String {
+    let mut csv = String::from("kind,verification,path,hash,old_archive,old_offset,old_size,old_compressed_size,new_archive,new_offset,new_size,new_compressed_size\n");
+    for change in changes {
+        let side = |metadata: &Option<Metadata>| match metadata {
+            Some(metadata) => format!("{},{},{},{}", crate::util::csv_field(&metadata.archive), metadata.offset, metadata.size, metadata.compressed_size),
+            None => ",,,".to_string(),
+        };
+        csv.push_str(&format!("{},{},{},{:08X},{},{}\n", change.kind.name().to_lowercase(), change.verification.name().to_lowercase(), crate::util::csv_field(&change.path), change.hash, side(&change.old), side(&change.new)));
+    }
+    csv
+}
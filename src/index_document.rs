@@ -0,0 +1,436 @@
+use std::io::Seek;
+
+use crate::files::bxon::Bxon;
+use crate::files::tp_archive_file_param::{self, ArchiveParam, FileParam};
+
+const ASSET_TYPE: &str = "tpArchiveFileParam";
+const BXON_HEADER_SIZE: u64 = 20;
+const INDEX_HEADER_SIZE: u64 = 16;
+// Records are aligned to 4 bytes, so these include the padding after them
+const ARCHIVE_RECORD_SIZE: u64 = 12;
+const FILE_RECORD_SIZE: u64 = 28;
+// Levels tried to reproduce the original zstd frame, higher ones are too slow to search
+const ZSTD_LEVELS: std::ops::RangeInclusive<i32> = 1..=9;
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct BxonHeader {
+    pub version: u32,
+    pub project_id: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub flags: u32,
+    pub is_streamed: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    pub hash: u32,
+    pub archive_index: u8,
+    // In 16 byte units, as stored
+    pub archive_offset: u32,
+    pub compressed_size: u32,
+    pub uncompressed_size: u32,
+    pub buffer_size: u32,
+    pub is_compressed: bool,
+}
+
+// Where everything sat in the original BXON, so an unedited document rebuilds byte for byte.
+// Bytes no field describes, e.g. non-zero padding, are kept as hex patches.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Layout {
+    pub size: u64,
+    pub asset_type_offset: u64,
+    pub asset_data_offset: u64,
+    pub archives_offset: u64,
+    pub files_offset: u64,
+    pub archive_name_offsets: Vec<u64>,
+    pub file_name_offsets: Vec<u64>,
+    #[serde(default)]
+    pub patches: Vec<Patch>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Patch {
+    pub offset: u64,
+    pub bytes: String,
+}
+
+// Editable form of an info.arc. Edits that no longer fit the recorded layout are written with a fresh one.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct IndexDocument {
+    pub bxon: BxonHeader,
+    // The info.arc is zstd compressed at this level, no level writes a bare BXON
+    pub zstd_level: Option<i32>,
+    pub archives: Vec<ArchiveEntry>,
+    pub files: Vec<FileEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn align(offset: u64, alignment: u64) -> u64 {
+    offset.div_ceil(alignment) * alignment
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, std::io::Error> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(invalid_data(format!("Patch \"{}\" is not a hex string.", hex)));
+    }
+    (0..hex.len()).step_by(2).map(|index| u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| invalid_data(format!("Patch \"{}\" is not a hex string.", hex)))).collect()
+}
+
+fn is_zstd(data: &[u8]) -> bool {
+    data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD])
+}
+
+// Output buffer that refuses writes outside it or over different bytes written before.
+// Identical bytes may overlap, names are sometimes shared between records.
+struct Image {
+    data: Vec<u8>,
+    occupied: Vec<bool>,
+}
+
+impl Image {
+    fn new(size: u64) -> Self {
+        Self {
+            data: vec![0; size as usize],
+            occupied: vec![false; size as usize],
+        }
+    }
+
+    fn place(&mut self, offset: u64, bytes: &[u8]) -> Option<()> {
+        let start = offset as usize;
+        let end = start.checked_add(bytes.len()).filter(|end| *end <= self.data.len())?;
+        if (start..end).any(|index| self.occupied[index] && self.data[index] != bytes[index - start]) {
+            return None;
+        }
+        self.data[start..end].copy_from_slice(bytes);
+        self.occupied[start..end].fill(true);
+        Some(())
+    }
+
+    fn place_u32(&mut self, offset: u64, value: u32) -> Option<()> {
+        self.place(offset, &value.to_le_bytes())
+    }
+
+    // Offsets are stored relative to the field holding them
+    fn place_offset(&mut self, offset: u64, target: u64) -> Option<()> {
+        self.place_u32(offset, u32::try_from(target.checked_sub(offset)?).ok()?)
+    }
+
+    fn place_string(&mut self, offset: u64, string: &str) -> Option<()> {
+        self.place(offset, string.as_bytes())?;
+        self.place(offset + string.len() as u64, &[0])
+    }
+
+    // Patches only fill bytes no field was written to
+    fn apply_patch(&mut self, offset: u64, bytes: &[u8]) {
+        for (index, byte) in bytes.iter().enumerate() {
+            let position = offset as usize + index;
+            if position < self.data.len() && !self.occupied[position] {
+                self.data[position] = *byte;
+            }
+        }
+    }
+}
+
+impl IndexDocument {
+    // Reads an info.arc, zstd compressed or not, and checks that the document rebuilds it exactly.
+    // Anything that would not round-trip is returned as warnings.
+    pub fn export(data: &[u8]) -> Result<(Self, Vec<String>), std::io::Error> {
+        let bxon_data = match is_zstd(data) {
+            true => zstd::stream::decode_all(data)?,
+            false => data.to_vec(),
+        };
+
+        let mut reader = std::io::Cursor::new(bxon_data.as_slice());
+        let bxon = Bxon::new(&mut reader)?;
+        if bxon.asset_type != ASSET_TYPE {
+            return Err(invalid_data(format!("Expected a {} index, found {}.", ASSET_TYPE, bxon.asset_type)));
+        }
+        reader.seek(std::io::SeekFrom::Start(bxon.offset_asset_data))?;
+        let (tp_archive_file_param, archive_params, file_params) = tp_archive_file_param::read_index(&mut reader)?;
+
+        let mut document = Self {
+            bxon: BxonHeader {
+                version: bxon.version,
+                project_id: bxon.project_id,
+            },
+            zstd_level: None,
+            archives: archive_params.iter().map(ArchiveEntry::from).collect(),
+            files: file_params.iter().map(FileEntry::from).collect(),
+            layout: Some(Layout {
+                size: bxon_data.len() as u64,
+                asset_type_offset: bxon.offset_asset_type,
+                asset_data_offset: bxon.offset_asset_data,
+                archives_offset: tp_archive_file_param.offset_archives,
+                files_offset: tp_archive_file_param.offset_files,
+                archive_name_offsets: archive_params.iter().map(|archive_param| archive_param.offset_name).collect(),
+                file_name_offsets: file_params.iter().map(|file_param| file_param.offset_name).collect(),
+                patches: Vec::new(),
+            }),
+        };
+
+        let mut warnings = Vec::new();
+        match document.write_bxon() {
+            Some(rebuilt) => {
+                document.layout.as_mut().unwrap().patches = diff_patches(&rebuilt, &bxon_data);
+                if document.write_bxon().as_deref() != Some(bxon_data.as_slice()) {
+                    warnings.push("Some fields hold values the document cannot express, the rebuilt BXON will differ.".to_string());
+                }
+            },
+            None => {
+                document.layout = None;
+                warnings.push("The original layout could not be recorded, the rebuilt BXON will be laid out afresh.".to_string());
+            }
+        }
+
+        if is_zstd(data) {
+            let level = ZSTD_LEVELS.into_iter().find(|level| zstd::bulk::compress(&bxon_data, *level).is_ok_and(|compressed| compressed == data));
+            if level.is_none() {
+                warnings.push(format!("No zstd level reproduces the original compression, level {} will be used and only the decompressed data will match.", DEFAULT_ZSTD_LEVEL));
+            }
+            document.zstd_level = Some(level.unwrap_or(DEFAULT_ZSTD_LEVEL));
+        }
+
+        Ok((document, warnings))
+    }
+
+    // The info.arc to write, using the recorded layout when the document still fits it.
+    // Names that no longer match their hash are returned as warnings, the hash is written as given.
+    pub fn build(&self) -> Result<(Vec<u8>, Vec<String>), std::io::Error> {
+        self.validate()?;
+        let bxon_data = match self.write_bxon() {
+            Some(bxon_data) => bxon_data,
+            None => self.with_layout(&self.fresh_layout()).ok_or_else(|| invalid_data("The index does not fit in 4 GiB.".to_string()))?,
+        };
+
+        let data = match self.zstd_level {
+            Some(level) => zstd::bulk::compress(&bxon_data, level)?,
+            None => bxon_data,
+        };
+        Ok((data, self.hash_warnings()))
+    }
+
+    fn hash_warnings(&self) -> Vec<String> {
+        let mismatched = self.files.iter().filter(|file| !crate::hash::verify(&file.name, file.hash)).collect::<Vec<_>>();
+        match mismatched.first() {
+            Some(file) => vec![format!("{} files have a hash that does not match their name, e.g. \"{}\" is stored as {:08X} but hashes to {:08X}.", mismatched.len(), file.name, file.hash, crate::hash::hash_path(&file.name))],
+            None => Vec::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), std::io::Error> {
+        if let Some(file) = self.files.iter().find(|file| file.archive_index as usize >= self.archives.len()) {
+            return Err(invalid_data(format!("{} refers to archive {} but there are only {}.", file.name, file.archive_index, self.archives.len())));
+        }
+        let names = self.archives.iter().map(|archive| &archive.name).chain(self.files.iter().map(|file| &file.name));
+        if let Some(name) = names.into_iter().find(|name| name.contains('\0')) {
+            return Err(invalid_data(format!("\"{}\" contains a null character.", name.escape_default())));
+        }
+        Ok(())
+    }
+
+    fn write_bxon(&self) -> Option<Vec<u8>> {
+        self.with_layout(self.layout.as_ref()?)
+    }
+
+    fn with_layout(&self, layout: &Layout) -> Option<Vec<u8>> {
+        if layout.archive_name_offsets.len() != self.archives.len() || layout.file_name_offsets.len() != self.files.len() {
+            return None;
+        }
+
+        let mut image = Image::new(layout.size);
+        image.place(0, b"BXON")?;
+        image.place_u32(4, self.bxon.version)?;
+        image.place_u32(8, self.bxon.project_id)?;
+        image.place_offset(12, layout.asset_type_offset)?;
+        image.place_offset(16, layout.asset_data_offset)?;
+        image.place_string(layout.asset_type_offset, ASSET_TYPE)?;
+
+        let header = layout.asset_data_offset;
+        image.place_u32(header, self.archives.len() as u32)?;
+        image.place_offset(header + 4, layout.archives_offset)?;
+        image.place_u32(header + 8, self.files.len() as u32)?;
+        image.place_offset(header + 12, layout.files_offset)?;
+
+        let mut position = layout.archives_offset;
+        for (archive, name_offset) in self.archives.iter().zip(layout.archive_name_offsets.iter().copied()) {
+            position = align(position, 4);
+            image.place_offset(position, name_offset)?;
+            image.place_u32(position + 4, archive.flags)?;
+            image.place(position + 8, &[archive.is_streamed as u8])?;
+            image.place_string(name_offset, &archive.name)?;
+            position += 9;
+        }
+
+        let mut position = layout.files_offset;
+        for (file, name_offset) in self.files.iter().zip(layout.file_name_offsets.iter().copied()) {
+            position = align(position, 4);
+            image.place_u32(position, file.hash)?;
+            image.place_offset(position + 4, name_offset)?;
+            image.place_u32(position + 8, file.archive_offset)?;
+            image.place_u32(position + 12, file.compressed_size)?;
+            image.place_u32(position + 16, file.uncompressed_size)?;
+            image.place_u32(position + 20, file.buffer_size)?;
+            image.place(position + 24, &[file.archive_index, file.is_compressed as u8])?;
+            image.place_string(name_offset, &file.name)?;
+            position += 26;
+        }
+
+        for patch in layout.patches.iter() {
+            image.apply_patch(patch.offset, &from_hex(&patch.bytes).ok()?);
+        }
+        Some(image.data)
+    }
+
+    // Header, asset type, record tables, then every name in record order
+    fn fresh_layout(&self) -> Layout {
+        let asset_type_offset = BXON_HEADER_SIZE;
+        let asset_data_offset = align(asset_type_offset + ASSET_TYPE.len() as u64 + 1, 16);
+        let archives_offset = asset_data_offset + INDEX_HEADER_SIZE;
+        let files_offset = archives_offset + ARCHIVE_RECORD_SIZE * self.archives.len() as u64;
+        let mut name_offset = files_offset + FILE_RECORD_SIZE * self.files.len() as u64;
+
+        let mut next_name = |name: &str| {
+            let offset = name_offset;
+            name_offset += name.len() as u64 + 1;
+            offset
+        };
+        let archive_name_offsets = self.archives.iter().map(|archive| next_name(&archive.name)).collect();
+        let file_name_offsets = self.files.iter().map(|file| next_name(&file.name)).collect();
+
+        Layout {
+            size: align(name_offset, 16),
+            asset_type_offset,
+            asset_data_offset,
+            archives_offset,
+            files_offset,
+            archive_name_offsets,
+            file_name_offsets,
+            patches: Vec::new(),
+        }
+    }
+}
+
+// Runs of bytes where `rebuilt` differs from `original`
+fn diff_patches(rebuilt: &[u8], original: &[u8]) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    let mut index = 0;
+    while index < original.len() {
+        if rebuilt[index] == original[index] {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < original.len() && rebuilt[index] != original[index] {
+            index += 1;
+        }
+        patches.push(Patch {
+            offset: start as u64,
+            bytes: to_hex(&original[start..index]),
+        });
+    }
+    patches
+}
+
+impl From<&ArchiveParam> for ArchiveEntry {
+    fn from(archive_param: &ArchiveParam) -> Self {
+        Self {
+            name: archive_param.name.clone(),
+            flags: archive_param.flags,
+            is_streamed: archive_param.is_streamed,
+        }
+    }
+}
+
+impl From<&FileParam> for FileEntry {
+    fn from(file_param: &FileParam) -> Self {
+        Self {
+            name: file_param.name.clone(),
+            hash: file_param.hash,
+            archive_index: file_param.archive_index,
+            archive_offset: file_param.archive_offset,
+            compressed_size: file_param.compressed_size,
+            uncompressed_size: file_param.uncompressed_size,
+            buffer_size: file_param.buffer_size,
+            is_compressed: file_param.is_compressed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, archive_offset: u32) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            hash: crate::hash::hash_path(name),
+            archive_index: 0,
+            archive_offset,
+            compressed_size: 0x120,
+            uncompressed_size: 0x300,
+            buffer_size: 0x40,
+            is_compressed: true,
+        }
+    }
+
+    fn document(zstd_level: Option<i32>) -> IndexDocument {
+        IndexDocument {
+            bxon: BxonHeader {
+                version: 3,
+                project_id: 0x4E494552,
+            },
+            zstd_level,
+            archives: vec![ArchiveEntry { name: "data000.arc".to_string(), flags: 0, is_streamed: false }],
+            files: vec![file("core/title.pack", 0), file("core/menu.pack", 0x12)],
+            layout: None,
+        }
+    }
+
+    fn round_trip(data: &[u8]) {
+        let (exported, warnings) = IndexDocument::export(data).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let json = serde_json::to_string(&exported).unwrap();
+        let (rebuilt, warnings) = serde_json::from_str::<IndexDocument>(&json).unwrap().build().unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(rebuilt, data);
+    }
+
+    #[test]
+    fn export_then_build_is_byte_identical() {
+        let (data, _) = document(None).build().unwrap();
+        round_trip(&data);
+    }
+
+    #[test]
+    fn export_then_build_keeps_padding_and_compression() {
+        let (mut data, _) = document(None).build().unwrap();
+        // Padding between the asset type and the asset data
+        data[BXON_HEADER_SIZE as usize + ASSET_TYPE.len() + 2] = 0xAA;
+        round_trip(&data);
+        round_trip(&zstd::bulk::compress(&data, DEFAULT_ZSTD_LEVEL).unwrap());
+    }
+
+    #[test]
+    fn build_reports_renamed_files() {
+        let mut document = document(None);
+        document.files[0].name = "core/renamed.pack".to_string();
+        let (_, warnings) = document.build().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("core/renamed.pack"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::{Arc, RwLock};
+
+use eframe::egui;
+
+struct Job {
+    title: String,
+    total: usize,
+    completed: usize,
+    failures: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct JobHandle(Arc<RwLock<Job>>);
+
+impl JobHandle {
+    pub fn add_total(&self, count: usize) {
+        self.0.write().unwrap().total += count;
+    }
+
+    pub fn complete(&self) {
+        self.0.write().unwrap().completed += 1;
+    }
+
+    pub fn fail(&self, message: String) {
+        println!("{}", message);
+        let mut job = self.0.write().unwrap();
+        job.completed += 1;
+        job.failures.push(message);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let job = self.0.read().unwrap();
+        job.completed >= job.total
+    }
+}
+
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<JobHandle>,
+}
+
+impl Jobs {
+    pub fn start(&mut self, title: String, total: usize) -> JobHandle {
+        let job = JobHandle(Arc::new(RwLock::new(Job {
+            title,
+            total,
+            completed: 0,
+            failures: Vec::new(),
+        })));
+        self.jobs.push(job.clone());
+        job
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn paint(&mut self, ctx: &egui::Context) {
+        let mut dismissed = Vec::new();
+        for (index, handle) in self.jobs.iter().enumerate() {
+            let job = handle.0.read().unwrap();
+            let finished = job.completed >= job.total;
+            let icon = if finished { egui_phosphor::regular::CHECK_CIRCLE } else { egui_phosphor::regular::HOURGLASS };
+
+            egui::Window::new(format!("{} {}", icon, job.title))
+                .id(egui::Id::new("job").with(Arc::as_ptr(&handle.0) as usize))
+                .collapsible(true)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.add(egui::ProgressBar::new(if job.total == 0 { 1.0 } else { job.completed as f32 / job.total as f32 }).text(format!("{}/{}", job.completed, job.total)));
+                    if !job.failures.is_empty() {
+                        ui.collapsing(format!("{} {} failed", egui_phosphor::regular::WARNING, job.failures.len()), |ui| {
+                            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                                for failure in job.failures.iter() {
+                                    ui.label(failure);
+                                }
+                            });
+                            if ui.button("Copy").clicked() {
+                                ui.ctx().copy_text(job.failures.join("\n"));
+                            }
+                        });
+                    }
+                    if finished {
+                        ui.vertical_centered(|ui| {
+                            if ui.button("Close").clicked() {
+                                dismissed.push(index);
+                            }
+                        });
+                    }
+                });
+
+            if !finished {
+                ctx.request_repaint();
+            }
+        }
+
+        for index in dismissed.into_iter().rev() {
+            self.jobs.remove(index);
+        }
+    }
+}
@@ -4,11 +4,33 @@
 use eframe::egui;
 
 mod app;
+mod cli;
 mod traits;
+mod data_export;
+mod dictionary;
+mod extract;
 mod files;
+mod filter;
+mod hash;
+mod index_diff;
+mod index_document;
+mod jobs;
+mod manifest;
+mod texture;
 mod util;
+mod vfs;
+mod widgets;
 
 fn main() -> eframe::Result {
+    let cli = <cli::Cli as clap::Parser>::parse();
+    if let Some(command) = cli.command {
+        if let Err(e) = cli::run(command) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default(),
         ..Default::default()
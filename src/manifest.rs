@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const MANIFEST_FILE_NAME: &str = "replicant_manifest.jsonl";
+
+// Where a file came from when it was extracted, a changed entry means the file changed in the game data
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub hash: u32,
+    pub archive: String,
+    pub offset: u64,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    // Missing from older manifests, those entries no longer match and are extracted again
+    #[serde(default)]
+    pub buffer_size: u64,
+}
+
+impl ManifestEntry {
+    // Length of the extracted file, the data followed by its buffer
+    pub fn extracted_size(&self) -> u64 {
+        self.uncompressed_size + self.buffer_size
+    }
+}
+
+// One JSON object per line, appended as soon as a file is written so an interrupted extraction can resume.
+// Later lines for the same name replace earlier ones.
+pub struct Manifest {
+    path: PathBuf,
+    entries: HashMap<String, ManifestEntry>,
+    // Only opened once something is recorded so dry runs leave no trace
+    writer: Mutex<Option<std::fs::File>>,
+}
+
+impl Manifest {
+    pub fn open(output_folder: &Path) -> Result<Self, std::io::Error> {
+        let path = output_folder.join(MANIFEST_FILE_NAME);
+        let mut entries = HashMap::new();
+        if path.exists() {
+            let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // A line cut short by an interruption is simply ignored
+                if let Ok(entry) = serde_json::from_str::<ManifestEntry>(&line) {
+                    entries.insert(entry.name.clone(), entry);
+                }
+            }
+        }
+
+        Ok(Self {
+            path,
+            entries,
+            writer: Mutex::new(None),
+        })
+    }
+
+    // True when the file was extracted from this exact entry before and is still on disk at its full length.
+    // Contents are not compared, a file edited without changing its length counts as current.
+    pub fn is_current(&self, output_folder: &Path, entry: &ManifestEntry) -> bool {
+        self.entries.get(&entry.name) == Some(entry)
+            && crate::extract::sanitize_path(&entry.name).is_ok_and(|path| std::fs::metadata(output_folder.join(path)).is_ok_and(|metadata| metadata.is_file() && metadata.len() == entry.extracted_size()))
+    }
+
+    pub fn record(&self, entry: &ManifestEntry) -> Result<(), std::io::Error> {
+        let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+
+        let mut writer = self.writer.lock().unwrap();
+        if writer.is_none() {
+            *writer = Some(std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+        let file = writer.as_mut().unwrap();
+        writeln!(file, "{}", line)?;
+        file.flush()
+    }
+}
@@ -0,0 +1,45 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Straight,
+    Premultiplied,
+}
+
+impl AlphaMode {
+    // DDS_ALPHA_MODE_* as stored in the DX10 header
+    pub fn from_dds(value: u32) -> Self {
+        match value {
+            2 => AlphaMode::Premultiplied,
+            _ => AlphaMode::Straight,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlphaMode::Straight => "Straight",
+            AlphaMode::Premultiplied => "Premultiplied",
+        }
+    }
+}
+
+// Fully transparent texels are left as they are, cutout textures often keep color there.
+pub fn unpremultiply(image: &image::RgbaImage) -> image::RgbaImage {
+    let mut straight = image.clone();
+    for pixel in straight.pixels_mut().filter(|pixel| pixel[3] > 0) {
+        let a = pixel[3] as u32;
+        for channel in 0..3 {
+            pixel[channel] = ((pixel[channel] as u32 * 255 + a / 2) / a).min(255) as u8;
+        }
+    }
+    straight
+}
+
+pub fn premultiply(image: &image::RgbaImage) -> image::RgbaImage {
+    let mut premultiplied = image.clone();
+    for pixel in premultiplied.pixels_mut() {
+        let a = pixel[3] as u32;
+        for channel in 0..3 {
+            pixel[channel] = ((pixel[channel] as u32 * a + 127) / 255) as u8;
+        }
+    }
+    premultiplied
+}
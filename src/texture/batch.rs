@@ -0,0 +1,133 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::extract;
+use crate::files::pack::PackManager;
+use crate::jobs::JobHandle;
+use crate::texture::export::{ExportFormat, ExportMips};
+
+#[derive(Clone, Copy)]
+pub struct BatchExportOptions {
+    pub format: ExportFormat,
+    pub mips: ExportMips,
+    // Same as the single texture export option, unpremultiplies PNG and TGA exports
+    pub straight_alpha: bool,
+}
+
+// Writes every texture of a PACK to `output_dir`, mirroring the file names inside the pack.
+// Returns the failed texture names and their errors, including names that would land outside `output_dir`.
+pub fn export_pack_textures(path: PathBuf, runtime: tokio::runtime::Handle, pack_bytes: &[u8], output_dir: &Path, options: BatchExportOptions) -> Result<Vec<(String, std::io::Error)>, std::io::Error> {
+    let pack = PackManager::new(path, runtime, std::io::Cursor::new(pack_bytes))?;
+
+    let mut failures = Vec::new();
+    for (name, texture) in pack.textures() {
+        let relative_path = match extract::sanitize_path(&format!("{}.{}", name, options.format.extension())) {
+            Ok(relative_path) => relative_path,
+            Err(e) => {
+                failures.push((name, e));
+                continue;
+            }
+        };
+        let output_path = output_dir.join(relative_path);
+        if let Err(e) = texture.write_export(&output_path, options.format, options.mips, options.straight_alpha) {
+            failures.push((name, e));
+        }
+    }
+    Ok(failures)
+}
+
+pub fn is_pack(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PACK")
+}
+
+// Checks the magic without reading the rest of the file
+fn is_pack_file(path: &Path) -> Result<bool, std::io::Error> {
+    let mut magic = [0; 4];
+    match std::fs::File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(is_pack(&magic)),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Decoding is CPU-bound, so batch exports only keep as many packs as there are cores in flight at once
+pub fn workers() -> Arc<tokio::sync::Semaphore> {
+    Arc::new(tokio::sync::Semaphore::new(std::thread::available_parallelism().map_or(4, |count| count.get())))
+}
+
+// Runs `export_pack_textures` for a pack and records the outcome on `job`
+pub fn export_pack_textures_job(path: PathBuf, runtime: tokio::runtime::Handle, pack_bytes: &[u8], output_dir: &Path, options: BatchExportOptions, job: &JobHandle) {
+    let name = path.to_str().unwrap_or_default().to_string();
+    match export_pack_textures(path, runtime, pack_bytes, output_dir, options) {
+        Ok(failures) if failures.is_empty() => job.complete(),
+        Ok(failures) => {
+            let message = failures.iter().map(|(texture, e)| format!("{}: {}", texture, e)).collect::<Vec<_>>().join(", ");
+            job.fail(format!("{}: {}", name, message));
+        },
+        Err(e) => job.fail(format!("{}: {}", name, e)),
+    }
+}
+
+// `job` is expected to start with a total of one step for scanning the folder
+pub fn export_folder_textures(runtime: tokio::runtime::Handle, input_dir: PathBuf, output_dir: PathBuf, options: BatchExportOptions, job: JobHandle) {
+    let mut files = Vec::new();
+    let mut directories = vec![input_dir.clone()];
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = directory.read_dir() else {
+            job.add_total(1);
+            job.fail(format!("Failed to read {}", directory.display()));
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    job.add_total(files.len());
+    job.complete();
+    let workers = workers();
+    for path in files {
+        let relative_path = path.strip_prefix(&input_dir).unwrap_or(&path).to_path_buf();
+        let output_dir = output_dir.join(&relative_path);
+        let job = job.clone();
+        let handle = runtime.clone();
+        let workers = workers.clone();
+        runtime.spawn(async move {
+            let Ok(_permit) = workers.acquire_owned().await else {
+                return;
+            };
+            let failed_job = job.clone();
+            let runtime = handle.clone();
+            let result = runtime.spawn_blocking(move || {
+                match is_pack_file(&path) {
+                    Ok(true) => {},
+                    Ok(false) => {
+                        job.complete();
+                        return;
+                    },
+                    Err(e) => {
+                        job.fail(format!("{}: {}", path.display(), e));
+                        return;
+                    }
+                }
+                let bytes = match std::fs::read(&path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        job.fail(format!("{}: {}", path.display(), e));
+                        return;
+                    }
+                };
+                export_pack_textures_job(relative_path, handle, &bytes, &output_dir, options, &job);
+            }).await;
+            if let Err(e) = result {
+                failed_job.fail(e.to_string());
+            }
+        });
+    }
+}
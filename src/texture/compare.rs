@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use eframe::egui;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompareSlot {
+    A,
+    B,
+}
+
+impl CompareSlot {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompareSlot::A => "A",
+            CompareSlot::B => "B",
+        }
+    }
+}
+
+// A decoded copy of a texture, so it can be compared after its manager is closed
+pub struct TextureSnapshot {
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub format: String,
+    pub mip_count: u32,
+    pub size: u32,
+    pub mips: Vec<image::RgbaImage>,
+}
+
+fn slot_id(slot: CompareSlot) -> egui::Id {
+    egui::Id::new("texture_compare").with(slot.name())
+}
+
+fn open_request_id() -> egui::Id {
+    egui::Id::new("texture_compare").with("open_request")
+}
+
+// Slots live in the egui context so any manager can fill them without knowing about the app
+pub fn set_slot(ctx: &egui::Context, slot: CompareSlot, snapshot: TextureSnapshot) {
+    ctx.data_mut(|data| {
+        data.insert_temp(slot_id(slot), Arc::new(snapshot));
+        data.insert_temp(open_request_id(), true);
+    });
+}
+
+pub fn get_slot(ctx: &egui::Context, slot: CompareSlot) -> Option<Arc<TextureSnapshot>> {
+    ctx.data(|data| data.get_temp(slot_id(slot)))
+}
+
+pub fn clear_slot(ctx: &egui::Context, slot: CompareSlot) {
+    ctx.data_mut(|data| data.remove::<Arc<TextureSnapshot>>(slot_id(slot)));
+}
+
+pub fn take_open_request(ctx: &egui::Context) -> bool {
+    ctx.data_mut(|data| data.remove_temp::<bool>(open_request_id()).unwrap_or(false))
+}
+
+// (field, A, B) for every header field that differs
+pub fn header_differences(a: &TextureSnapshot, b: &TextureSnapshot) -> Vec<(&'static str, String, String)> {
+    let fields = [
+        ("Width", a.width.to_string(), b.width.to_string()),
+        ("Height", a.height.to_string(), b.height.to_string()),
+        ("Depth", a.depth.to_string(), b.depth.to_string()),
+        ("Format", a.format.clone(), b.format.clone()),
+        ("Mip Count", a.mip_count.to_string(), b.mip_count.to_string()),
+        ("Size", a.size.to_string(), b.size.to_string()),
+    ];
+    fields.into_iter().filter(|(_, a, b)| a != b).collect()
+}
+
+pub struct Difference {
+    pub heatmap: image::RgbaImage,
+    pub max: u8,
+    pub mean: f32,
+    pub differing_pixels: usize,
+}
+
+fn heat(value: f32) -> [u8; 3] {
+    // Black -> red -> yellow -> white
+    let value = value.clamp(0.0, 1.0) * 3.0;
+    let r = value.min(1.0);
+    let g = (value - 1.0).clamp(0.0, 1.0);
+    let b = (value - 2.0).clamp(0.0, 1.0);
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
+
+// Per pixel absolute difference of the largest differing channel, scaled by `gain` for the heatmap.
+// `b` is resized to `a` when they differ in size.
+pub fn difference(a: &image::RgbaImage, b: &image::RgbaImage, gain: f32) -> Difference {
+    let resized;
+    let b = match a.dimensions() == b.dimensions() {
+        true => b,
+        false => {
+            resized = image::imageops::resize(b, a.width(), a.height(), image::imageops::FilterType::Nearest);
+            &resized
+        }
+    };
+
+    let mut heatmap = image::RgbaImage::new(a.width(), a.height());
+    let mut max = 0;
+    let mut total = 0u64;
+    let mut differing_pixels = 0;
+    for ((pixel_a, pixel_b), output) in a.pixels().zip(b.pixels()).zip(heatmap.pixels_mut()) {
+        let delta = pixel_a.0.iter().zip(pixel_b.0.iter()).map(|(a, b)| a.abs_diff(*b)).max().unwrap_or(0);
+        max = max.max(delta);
+        total += delta as u64;
+        if delta > 0 {
+            differing_pixels += 1;
+        }
+        let [r, g, b] = heat(delta as f32 / 255.0 * gain);
+        *output = image::Rgba([r, g, b, 255]);
+    }
+
+    let pixel_count = (a.width() as u64 * a.height() as u64).max(1);
+    Difference {
+        heatmap,
+        max,
+        mean: total as f32 / pixel_count as f32,
+        differing_pixels,
+    }
+}
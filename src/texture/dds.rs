@@ -0,0 +1,72 @@
+use std::io::Write;
+use byteorder::WriteBytesExt;
+
+// DX9-style pixel formats, for tools that cannot read the DX10 header extension
+#[derive(Clone, Copy)]
+pub enum LegacyPixelFormat {
+    FourCC([u8; 4]),
+    D3dFormat(u32),
+    Rgba8,
+    Luminance8,
+}
+
+impl LegacyPixelFormat {
+    fn is_compressed(&self) -> bool {
+        matches!(self, LegacyPixelFormat::FourCC(_))
+    }
+}
+
+pub fn write_legacy_dds(width: u32, height: u32, depth: u32, mip_count: u32, pitch_or_linear_size: u32, pixel_format: LegacyPixelFormat, data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut dds_bytes = Vec::new();
+    dds_bytes.write_all(b"DDS\x20")?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(124)?;
+    let mut flags = 0x1 | 0x2 | 0x4 | 0x1000;
+    flags |= if pixel_format.is_compressed() { 0x80000 } else { 0x8 };
+    if mip_count > 1 {
+        flags |= 0x20000;
+    }
+    if depth > 1 {
+        flags |= 0x800000;
+    }
+    dds_bytes.write_u32::<byteorder::LittleEndian>(flags)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(height)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(width)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(pitch_or_linear_size)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(depth)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(mip_count)?;
+    for _ in 0..11 {
+        dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    }
+
+    // DDS Pixel Format
+    let (pixel_flags, four_cc, bit_count, masks): (u32, [u8; 4], u32, [u32; 4]) = match pixel_format {
+        LegacyPixelFormat::FourCC(four_cc) => (0x4, four_cc, 0, [0; 4]),
+        LegacyPixelFormat::D3dFormat(format) => (0x4, format.to_le_bytes(), 0, [0; 4]),
+        LegacyPixelFormat::Rgba8 => (0x40 | 0x1, [0; 4], 32, [0x000000FF, 0x0000FF00, 0x00FF0000, 0xFF000000]),
+        LegacyPixelFormat::Luminance8 => (0x20000, [0; 4], 8, [0xFF, 0, 0, 0]),
+    };
+    dds_bytes.write_u32::<byteorder::LittleEndian>(32)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(pixel_flags)?;
+    dds_bytes.write_all(&four_cc)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(bit_count)?;
+    for mask in masks {
+        dds_bytes.write_u32::<byteorder::LittleEndian>(mask)?;
+    }
+
+    let mut caps = 0x1000;
+    if mip_count > 1 {
+        caps |= 0x8 | 0x400000;
+    }
+    dds_bytes.write_u32::<byteorder::LittleEndian>(caps)?;
+    caps = 0x0;
+    if depth > 1 {
+        caps |= 0x200000;
+    }
+    dds_bytes.write_u32::<byteorder::LittleEndian>(caps)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    dds_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+
+    dds_bytes.write_all(data)?;
+    Ok(dds_bytes)
+}
@@ -0,0 +1,60 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Dds,
+    DdsLegacy,
+    Ktx2,
+    Png,
+    Tga,
+    Exr,
+    Hdr,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 7] = [
+        ExportFormat::Dds,
+        ExportFormat::DdsLegacy,
+        ExportFormat::Ktx2,
+        ExportFormat::Png,
+        ExportFormat::Tga,
+        ExportFormat::Exr,
+        ExportFormat::Hdr,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Dds => "DDS (DX10 header)",
+            ExportFormat::DdsLegacy => "DDS (legacy DX9 header)",
+            ExportFormat::Ktx2 => "KTX2",
+            ExportFormat::Png => "PNG",
+            ExportFormat::Tga => "TGA",
+            ExportFormat::Exr => "OpenEXR",
+            ExportFormat::Hdr => "Radiance HDR",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Dds | ExportFormat::DdsLegacy => "dds",
+            ExportFormat::Ktx2 => "ktx2",
+            ExportFormat::Png => "png",
+            ExportFormat::Tga => "tga",
+            ExportFormat::Exr => "exr",
+            ExportFormat::Hdr => "hdr",
+        }
+    }
+
+    // Image formats hold a single decoded mip, containers hold the raw surface data with every mip
+    pub fn is_image(&self) -> bool {
+        !matches!(self, ExportFormat::Dds | ExportFormat::DdsLegacy | ExportFormat::Ktx2)
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, ExportFormat::Exr | ExportFormat::Hdr)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportMips {
+    Single(usize),
+    All,
+}
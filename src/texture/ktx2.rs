@@ -0,0 +1,173 @@
+use std::io::Write;
+use byteorder::WriteBytesExt;
+
+const IDENTIFIER: [u8; 12] = [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n'];
+
+pub const VK_FORMAT_R8_UNORM: u32 = 9;
+pub const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+pub const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+pub const VK_FORMAT_R32G32B32A32_SFLOAT: u32 = 109;
+pub const VK_FORMAT_BC1_RGBA_UNORM_BLOCK: u32 = 133;
+pub const VK_FORMAT_BC1_RGBA_SRGB_BLOCK: u32 = 134;
+pub const VK_FORMAT_BC2_UNORM_BLOCK: u32 = 135;
+pub const VK_FORMAT_BC2_SRGB_BLOCK: u32 = 136;
+pub const VK_FORMAT_BC3_UNORM_BLOCK: u32 = 137;
+pub const VK_FORMAT_BC3_SRGB_BLOCK: u32 = 138;
+pub const VK_FORMAT_BC4_UNORM_BLOCK: u32 = 139;
+pub const VK_FORMAT_BC5_UNORM_BLOCK: u32 = 141;
+pub const VK_FORMAT_BC6H_UFLOAT_BLOCK: u32 = 143;
+pub const VK_FORMAT_BC7_UNORM_BLOCK: u32 = 145;
+pub const VK_FORMAT_BC7_SRGB_BLOCK: u32 = 146;
+
+const KHR_DF_MODEL_RGBSDA: u8 = 1;
+const KHR_DF_MODEL_BC1A: u8 = 128;
+const KHR_DF_MODEL_BC2: u8 = 129;
+const KHR_DF_MODEL_BC3: u8 = 130;
+const KHR_DF_MODEL_BC4: u8 = 131;
+const KHR_DF_MODEL_BC5: u8 = 132;
+const KHR_DF_MODEL_BC6H: u8 = 133;
+const KHR_DF_MODEL_BC7: u8 = 134;
+
+const KHR_DF_SAMPLE_DATATYPE_FLOAT: u8 = 0x80;
+const KHR_DF_SAMPLE_DATATYPE_SIGNED: u8 = 0x40;
+
+struct Sample {
+    channel: u8,
+    bit_offset: u16,
+    bit_length: u8,
+    lower: u32,
+    upper: u32,
+}
+
+impl Sample {
+    fn new(channel: u8, bit_offset: u16, bit_length: u8) -> Self {
+        let upper = if bit_length >= 32 { u32::MAX } else { (1 << bit_length) - 1 };
+        Self { channel, bit_offset, bit_length, lower: 0, upper }
+    }
+
+    fn float(channel: u8, bit_offset: u16, bit_length: u8, signed: bool) -> Self {
+        let mut channel = channel | KHR_DF_SAMPLE_DATATYPE_FLOAT;
+        if signed {
+            channel |= KHR_DF_SAMPLE_DATATYPE_SIGNED;
+        }
+        Self { channel, bit_offset, bit_length, lower: if signed { (-1.0f32).to_bits() } else { 0 }, upper: 1.0f32.to_bits() }
+    }
+}
+
+// (color model, srgb, block dimension, bytes per block, type size, samples)
+fn describe(vk_format: u32) -> Option<(u8, bool, u8, u8, u32, Vec<Sample>)> {
+    let rgba8 = || vec![Sample::new(0, 0, 8), Sample::new(1, 8, 8), Sample::new(2, 16, 8), Sample::new(15, 24, 8)];
+    let description = match vk_format {
+        VK_FORMAT_R8_UNORM => (KHR_DF_MODEL_RGBSDA, false, 1, 1, 1, vec![Sample::new(0, 0, 8)]),
+        VK_FORMAT_R8G8B8A8_UNORM => (KHR_DF_MODEL_RGBSDA, false, 1, 4, 1, rgba8()),
+        VK_FORMAT_R8G8B8A8_SRGB => (KHR_DF_MODEL_RGBSDA, true, 1, 4, 1, rgba8()),
+        VK_FORMAT_R32G32B32A32_SFLOAT => (KHR_DF_MODEL_RGBSDA, false, 1, 16, 4, vec![
+            Sample::float(0, 0, 32, true),
+            Sample::float(1, 32, 32, true),
+            Sample::float(2, 64, 32, true),
+            Sample::float(15, 96, 32, true),
+        ]),
+        VK_FORMAT_BC1_RGBA_UNORM_BLOCK => (KHR_DF_MODEL_BC1A, false, 4, 8, 1, vec![Sample::new(1, 0, 64)]),
+        VK_FORMAT_BC1_RGBA_SRGB_BLOCK => (KHR_DF_MODEL_BC1A, true, 4, 8, 1, vec![Sample::new(1, 0, 64)]),
+        VK_FORMAT_BC2_UNORM_BLOCK => (KHR_DF_MODEL_BC2, false, 4, 16, 1, vec![Sample::new(15, 0, 64), Sample::new(0, 64, 64)]),
+        VK_FORMAT_BC2_SRGB_BLOCK => (KHR_DF_MODEL_BC2, true, 4, 16, 1, vec![Sample::new(15, 0, 64), Sample::new(0, 64, 64)]),
+        VK_FORMAT_BC3_UNORM_BLOCK => (KHR_DF_MODEL_BC3, false, 4, 16, 1, vec![Sample::new(15, 0, 64), Sample::new(0, 64, 64)]),
+        VK_FORMAT_BC3_SRGB_BLOCK => (KHR_DF_MODEL_BC3, true, 4, 16, 1, vec![Sample::new(15, 0, 64), Sample::new(0, 64, 64)]),
+        VK_FORMAT_BC4_UNORM_BLOCK => (KHR_DF_MODEL_BC4, false, 4, 8, 1, vec![Sample::new(0, 0, 64)]),
+        VK_FORMAT_BC5_UNORM_BLOCK => (KHR_DF_MODEL_BC5, false, 4, 16, 1, vec![Sample::new(0, 0, 64), Sample::new(1, 64, 64)]),
+        VK_FORMAT_BC6H_UFLOAT_BLOCK => (KHR_DF_MODEL_BC6H, false, 4, 16, 1, vec![Sample::float(0, 0, 128, false)]),
+        VK_FORMAT_BC7_UNORM_BLOCK => (KHR_DF_MODEL_BC7, false, 4, 16, 1, vec![Sample::new(0, 0, 128)]),
+        VK_FORMAT_BC7_SRGB_BLOCK => (KHR_DF_MODEL_BC7, true, 4, 16, 1, vec![Sample::new(0, 0, 128)]),
+        _ => return None,
+    };
+    Some(description)
+}
+
+fn write_data_format_descriptor(vk_format: u32, premultiplied: bool) -> Result<Vec<u8>, std::io::Error> {
+    let Some((color_model, srgb, block_dimension, bytes_per_block, _, samples)) = describe(vk_format) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("VkFormat {} has no KTX2 descriptor.", vk_format)));
+    };
+
+    let block_size = 24 + 16 * samples.len() as u32;
+    let mut dfd = Vec::new();
+    dfd.write_u32::<byteorder::LittleEndian>(4 + block_size)?;
+    dfd.write_u32::<byteorder::LittleEndian>(0)?;
+    dfd.write_u32::<byteorder::LittleEndian>(2 | (block_size << 16))?;
+    dfd.write_u8(color_model)?;
+    dfd.write_u8(1)?;
+    dfd.write_u8(if srgb { 2 } else { 1 })?;
+    dfd.write_u8(if premultiplied { 1 } else { 0 })?;
+    dfd.write_all(&[block_dimension - 1, block_dimension - 1, 0, 0])?;
+    dfd.write_all(&[bytes_per_block, 0, 0, 0, 0, 0, 0, 0])?;
+    for sample in samples {
+        dfd.write_u16::<byteorder::LittleEndian>(sample.bit_offset)?;
+        dfd.write_u8(sample.bit_length - 1)?;
+        dfd.write_u8(sample.channel)?;
+        dfd.write_u32::<byteorder::LittleEndian>(0)?;
+        dfd.write_u32::<byteorder::LittleEndian>(sample.lower)?;
+        dfd.write_u32::<byteorder::LittleEndian>(sample.upper)?;
+    }
+    Ok(dfd)
+}
+
+// `levels` are ordered from the base level down to the smallest mip
+pub fn write_ktx2(vk_format: u32, width: u32, height: u32, depth: u32, premultiplied: bool, levels: &[&[u8]]) -> Result<Vec<u8>, std::io::Error> {
+    let Some((_, _, _, bytes_per_block, type_size, _)) = describe(vk_format) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("VkFormat {} is not supported.", vk_format)));
+    };
+    let dfd = write_data_format_descriptor(vk_format, premultiplied)?;
+
+    let header_size = 12 + 4 * 9 + 4 * 4 + 8 * 2;
+    let level_index_size = 24 * levels.len();
+    let dfd_offset = header_size + level_index_size;
+
+    // Level data is stored smallest mip first, each aligned to lcm(texel block size, 4)
+    let alignment = match bytes_per_block % 4 {
+        0 => bytes_per_block as usize,
+        2 => bytes_per_block as usize * 2,
+        _ => bytes_per_block as usize * 4,
+    };
+    let mut level_offsets = vec![0; levels.len()];
+    let mut offset = dfd_offset + dfd.len();
+    for (level, data) in levels.iter().enumerate().rev() {
+        offset = offset.div_ceil(alignment) * alignment;
+        level_offsets[level] = offset;
+        offset += data.len();
+    }
+
+    let mut ktx2_bytes = Vec::new();
+    ktx2_bytes.write_all(&IDENTIFIER)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(vk_format)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(type_size)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(width)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(height)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(if depth > 1 { depth } else { 0 })?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(1)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(levels.len() as u32)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+
+    // Index
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(dfd_offset as u32)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(dfd.len() as u32)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    ktx2_bytes.write_u32::<byteorder::LittleEndian>(0)?;
+    ktx2_bytes.write_u64::<byteorder::LittleEndian>(0)?;
+    ktx2_bytes.write_u64::<byteorder::LittleEndian>(0)?;
+
+    // Level index
+    for (level, data) in levels.iter().enumerate() {
+        ktx2_bytes.write_u64::<byteorder::LittleEndian>(level_offsets[level] as u64)?;
+        ktx2_bytes.write_u64::<byteorder::LittleEndian>(data.len() as u64)?;
+        ktx2_bytes.write_u64::<byteorder::LittleEndian>(data.len() as u64)?;
+    }
+
+    ktx2_bytes.write_all(&dfd)?;
+
+    for (level, data) in levels.iter().enumerate().rev() {
+        ktx2_bytes.resize(level_offsets[level], 0);
+        ktx2_bytes.write_all(data)?;
+    }
+
+    Ok(ktx2_bytes)
+}
@@ -0,0 +1,7 @@
+pub mod alpha;
+pub mod batch;
+pub mod compare;
+pub mod dds;
+pub mod export;
+pub mod ktx2;
+pub mod normal_map;
@@ -0,0 +1,21 @@
+// Two-channel tangent-space normal maps store X and Y in R and G, Z is implied by the unit length.
+pub fn reconstruct_z(image: &image::RgbaImage) -> image::RgbaImage {
+    let mut normal_map = image.clone();
+    for pixel in normal_map.pixels_mut() {
+        let x = pixel[0] as f32 / 255.0 * 2.0 - 1.0;
+        let y = pixel[1] as f32 / 255.0 * 2.0 - 1.0;
+        let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+        pixel[2] = ((z * 0.5 + 0.5) * 255.0).round() as u8;
+        pixel[3] = 255;
+    }
+    normal_map
+}
+
+pub fn drop_z(image: &image::RgbaImage) -> image::RgbaImage {
+    let mut two_channel = image.clone();
+    for pixel in two_channel.pixels_mut() {
+        pixel[2] = 0;
+        pixel[3] = 255;
+    }
+    two_channel
+}
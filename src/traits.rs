@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 
+use crate::data_export::DataExport;
+use crate::files::tp_gx_tex_head::TpGxTexHeadManager;
 
+// A file read out of an open file, keyed by a virtual path such as "info.arc!/path/in/archive"
+pub type PendingOpen = (PathBuf, Result<Vec<u8>, std::io::Error>);
 
 pub trait Manager {
     fn path(&self) -> &PathBuf;
@@ -20,6 +24,16 @@ pub trait Manager {
     fn paint_floating(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
 
     }
+
+    // Files read out of this one that should be opened as their own entries
+    fn take_pending_opens(&mut self) -> Vec<PendingOpen> {
+        Vec::new()
+    }
+
+    // Parsed structures for JSON, YAML and CSV export, None for files that are not parsed
+    fn data_export(&self) -> Result<Option<DataExport>, std::io::Error> {
+        Ok(None)
+    }
 }
 
 pub trait Resource {
@@ -34,6 +48,10 @@ pub trait Resource {
     fn resource_preview(&mut self, ui: &mut eframe::egui::Ui, toasts: &mut egui_notify::Toasts) {
 
     }
+
+    fn as_texture(&self) -> Option<&TpGxTexHeadManager> {
+        None
+    }
 }
 
 pub trait ResourceManager: Resource + Manager {}
\ No newline at end of file
@@ -24,4 +24,26 @@ pub trait ReadUtilExt: std::io::Read + std::io::Seek {
     }
 }
 
-impl<R: std::io::Read + std::io::Seek + ?Sized> ReadUtilExt for R {}
\ No newline at end of file
+impl<R: std::io::Read + std::io::Seek + ?Sized> ReadUtilExt for R {}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
+
+// Quotes a CSV field when it contains a separator, quote or line break
+pub fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
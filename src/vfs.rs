@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::dictionary;
+use crate::filter::FilterEntry;
+use crate::files::bxon::Bxon;
+use crate::files::tp_archive_file_param::{self, ArchiveParam, FileParam};
+use crate::manifest::ManifestEntry;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Metadata {
+    pub path: String,
+    pub hash: u32,
+    pub archive: String,
+    pub offset: u64,
+    pub size: u64,
+    // Bytes `read` returns, the file followed by its buffer
+    pub extracted_size: u64,
+    pub compressed_size: u64,
+    pub is_compressed: bool,
+}
+
+impl Metadata {
+    pub fn filter_entry(&self) -> FilterEntry<'_> {
+        FilterEntry {
+            path: &self.path,
+            hash: self.hash,
+            archive: Some(&self.archive),
+            size: Some(self.size),
+            compressed_size: Some(self.compressed_size),
+            compressed: Some(self.is_compressed),
+        }
+    }
+
+    pub fn manifest_entry(&self) -> ManifestEntry {
+        ManifestEntry {
+            name: self.path.clone(),
+            hash: self.hash,
+            archive: self.archive.clone(),
+            offset: self.offset,
+            compressed_size: self.compressed_size,
+            uncompressed_size: self.size,
+            buffer_size: self.extracted_size - self.size,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+// Read-only view of an install, mounted through its info.arc index.
+// Archives are only touched when a file inside them is read.
+pub struct Vfs {
+    root: PathBuf,
+    archive_params: Vec<ArchiveParam>,
+    file_params: Vec<FileParam>,
+
+    by_path: HashMap<String, usize>,
+    by_hash: HashMap<u32, usize>,
+    directories: BTreeMap<String, BTreeSet<DirEntry>>,
+
+    // Non-streamed archives are one zstd frame, decompressed whole on first access.
+    // Only the most recently used ones stay resident, oldest first.
+    archives: Mutex<VecDeque<(usize, Arc<Vec<u8>>)>>,
+}
+
+// Decompressed archives can be hundreds of megabytes, reading files in archive order keeps this enough
+pub(crate) const MAX_CACHED_ARCHIVES: usize = 2;
+
+// Lowercase, forward slashes, no leading or trailing separators
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").split('/').filter(|part| !part.is_empty() && *part != ".").collect::<Vec<_>>().join("/").to_lowercase()
+}
+
+impl Vfs {
+    pub fn mount(info_path: &Path) -> Result<Self, std::io::Error> {
+        let info_file = std::fs::File::open(info_path)?;
+        let mut decoder = zstd::stream::Decoder::new(info_file)?;
+        let mut decompressed_data = Vec::new();
+        decoder.read_to_end(&mut decompressed_data)?;
+
+        let mut reader = std::io::Cursor::new(decompressed_data);
+        let bxon = Bxon::new(&mut reader)?;
+        if bxon.asset_type != "tpArchiveFileParam" {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Expected a tpArchiveFileParam index, found {}.", bxon.asset_type)));
+        }
+        reader.seek(std::io::SeekFrom::Start(bxon.offset_asset_data))?;
+        let (_, archive_params, file_params) = tp_archive_file_param::read_index(&mut reader)?;
+
+        let root = info_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(Self::new(root, archive_params, file_params))
+    }
+
+    fn new(root: PathBuf, archive_params: Vec<ArchiveParam>, file_params: Vec<FileParam>) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_hash = HashMap::new();
+        let mut directories: BTreeMap<String, BTreeSet<DirEntry>> = BTreeMap::new();
+        directories.insert(String::new(), BTreeSet::new());
+
+        for (index, file_param) in file_params.iter().enumerate() {
+            dictionary::observe(&file_param.name, file_param.hash);
+            by_hash.entry(file_param.hash).or_insert(index);
+
+            let path = normalize(&file_param.display_name());
+            by_path.entry(path.clone()).or_insert(index);
+
+            // Register every ancestor directory so empty intermediate levels can be listed
+            let mut parent = String::new();
+            let parts = path.split('/').collect::<Vec<_>>();
+            for (depth, part) in parts.iter().enumerate() {
+                let is_dir = depth + 1 < parts.len();
+                directories.entry(parent.clone()).or_default().insert(DirEntry { name: part.to_string(), is_dir });
+                if is_dir {
+                    parent = match parent.is_empty() {
+                        true => part.to_string(),
+                        false => format!("{}/{}", parent, part),
+                    };
+                }
+            }
+        }
+
+        Self {
+            root,
+            archive_params,
+            file_params,
+            by_path,
+            by_hash,
+            directories,
+            archives: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.file_params.len()
+    }
+
+    pub fn list(&self, dir: &str) -> Option<Vec<DirEntry>> {
+        self.directories.get(&normalize(dir)).map(|entries| entries.iter().cloned().collect())
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = String> + '_ {
+        self.file_params.iter().map(FileParam::display_name)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = Metadata> + '_ {
+        self.file_params.iter().map(|file_param| self.metadata(file_param))
+    }
+
+    // Paths that are not in the index are tried as a hash, so "0x1234ABCD" works too
+    fn lookup(&self, path: &str) -> Option<&FileParam> {
+        let index = self.by_path.get(&normalize(path))
+            .or_else(|| self.by_hash.get(&crate::hash::hash_path(&normalize(path))))
+            .or_else(|| crate::hash::parse_hash(path).and_then(|hash| self.by_hash.get(&hash)))?;
+        self.file_params.get(*index)
+    }
+
+    fn not_found(path: &str) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::NotFound, format!("\"{}\" is not in the index.", path))
+    }
+
+    // Only files have metadata, directories are listed with `list`
+    pub fn stat(&self, path: &str) -> Result<Metadata, std::io::Error> {
+        if self.directories.contains_key(&normalize(path)) {
+            return Err(std::io::Error::new(std::io::ErrorKind::IsADirectory, format!("\"{}\" is a directory.", path)));
+        }
+        let file_param = self.lookup(path).ok_or_else(|| Self::not_found(path))?;
+        Ok(self.metadata(file_param))
+    }
+
+    fn metadata(&self, file_param: &FileParam) -> Metadata {
+        Metadata {
+            path: file_param.display_name(),
+            hash: file_param.hash,
+            archive: self.archive_params.get(file_param.archive_index as usize).map(|archive_param| archive_param.name.clone()).unwrap_or_default(),
+            offset: file_param.offset(),
+            size: file_param.uncompressed_size as u64,
+            extracted_size: file_param.extracted_size(),
+            compressed_size: file_param.compressed_size as u64,
+            is_compressed: file_param.is_compressed,
+        }
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, std::io::Error> {
+        let file_param = self.lookup(path).ok_or_else(|| Self::not_found(path))?;
+        let archive_index = file_param.archive_index as usize;
+        let archive_param = self.archive_params.get(archive_index).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Archive index {} out of range.", archive_index)))?;
+        let archive_path = self.root.join(&archive_param.name);
+
+        match archive_param.is_streamed {
+            true => {
+                let archive_file = std::io::BufReader::new(std::fs::File::open(&archive_path)?);
+                tp_archive_file_param::read_archived_file(archive_file, file_param.offset(), file_param.compressed_size as usize, file_param.uncompressed_size as usize, file_param.buffer_size as usize, file_param.is_compressed)
+            },
+            false => {
+                let archive = self.archive_data(archive_index, &archive_path)?;
+                tp_archive_file_param::read_archived_file(std::io::Cursor::new(archive.as_slice()), file_param.offset(), file_param.compressed_size as usize, file_param.uncompressed_size as usize, file_param.buffer_size as usize, file_param.is_compressed)
+            }
+        }
+    }
+
+    // The file is read and decompressed whole up front, nothing is streamed from the archive
+    pub fn open(&self, path: &str) -> Result<impl Read + Seek, std::io::Error> {
+        Ok(std::io::Cursor::new(self.read(path)?))
+    }
+
+    // Holds the cache lock while decompressing, so concurrent readers of one archive wait instead of decompressing it twice
+    fn archive_data(&self, archive_index: usize, archive_path: &Path) -> Result<Arc<Vec<u8>>, std::io::Error> {
+        let mut archives = self.archives.lock().unwrap();
+        if let Some(position) = archives.iter().position(|(index, _)| *index == archive_index) {
+            let entry = archives.remove(position).unwrap();
+            let data = entry.1.clone();
+            archives.push_back(entry);
+            return Ok(data);
+        }
+
+        let archive_file = std::fs::File::open(archive_path)?;
+        let mut decoder = zstd::stream::Decoder::new(archive_file)?;
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data)?;
+
+        let data = Arc::new(data);
+        if archives.len() >= MAX_CACHED_ARCHIVES {
+            archives.pop_front();
+        }
+        archives.push_back((archive_index, data.clone()));
+        Ok(data)
+    }
+}
@@ -0,0 +1,67 @@
+use eframe::egui;
+
+use crate::texture::{batch::BatchExportOptions, export::{ExportFormat, ExportMips}};
+
+pub struct BatchExportDialog {
+    open: bool,
+    format: ExportFormat,
+    all_mips: bool,
+    straight_alpha: bool,
+}
+
+impl Default for BatchExportDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            format: ExportFormat::Png,
+            all_mips: false,
+            straight_alpha: false,
+        }
+    }
+}
+
+impl BatchExportDialog {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    // Returns the chosen options once the user confirms
+    pub fn show(&mut self, ctx: &egui::Context, id: egui::Id, source: &str) -> Option<BatchExportOptions> {
+        if !self.open {
+            return None;
+        }
+
+        let mut confirmed = false;
+        let mut open = self.open;
+        egui::Window::new(format!("{} Export textures", egui_phosphor::regular::IMAGES))
+            .id(id)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Source: {}", source));
+                egui::ComboBox::from_label("Format")
+                    .selected_text(self.format.name())
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::ALL.into_iter().filter(|format| !format.is_hdr()) {
+                            ui.selectable_value(&mut self.format, format, format.name());
+                        }
+                    });
+                ui.radio_value(&mut self.all_mips, false, "Top mip only");
+                ui.radio_value(&mut self.all_mips, true, "All mips");
+                if self.format.is_image() {
+                    ui.checkbox(&mut self.straight_alpha, "Convert premultiplied alpha to straight");
+                }
+
+                ui.separator();
+                confirmed = ui.button("Export…").clicked();
+            });
+
+        self.open = open && !confirmed;
+        confirmed.then_some(BatchExportOptions {
+            format: self.format,
+            mips: if self.all_mips { ExportMips::All } else { ExportMips::Single(0) },
+            straight_alpha: self.straight_alpha,
+        })
+    }
+}
@@ -0,0 +1,287 @@
+use std::io::Write;
+
+use eframe::egui;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ComponentType {
+    U8,
+    U32,
+    F32,
+}
+
+impl ComponentType {
+    fn name(&self) -> &'static str {
+        match self {
+            ComponentType::U8 => "u8",
+            ComponentType::U32 => "u32",
+            ComponentType::F32 => "f32",
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ComponentType::U8 => 1,
+            ComponentType::U32 | ComponentType::F32 => 4,
+        }
+    }
+}
+
+// Uncompressed texel data of a single mip, read as-is without going through a DDS decoder
+pub struct DataTexels<'a> {
+    pub data: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub channels: usize,
+    pub component: ComponentType,
+}
+
+impl DataTexels<'_> {
+    fn value(&self, component: ComponentType, x: u32, y: u32, channel: usize) -> Option<f64> {
+        let index = ((y as usize * self.width as usize + x as usize) * self.channels + channel) * component.size();
+        let bytes = self.data.get(index..index + component.size())?;
+        Some(match component {
+            ComponentType::U8 => bytes[0] as f64,
+            ComponentType::U32 => u32::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ComponentType::F32 => f32::from_le_bytes(bytes.try_into().ok()?) as f64,
+        })
+    }
+
+    fn format_value(&self, component: ComponentType, x: u32, y: u32, channel: usize) -> String {
+        match (component, self.value(component, x, y, channel)) {
+            (_, None) => "-".to_string(),
+            (ComponentType::F32, Some(value)) => format!("{:.4}", value),
+            (_, Some(value)) => format!("{}", value),
+        }
+    }
+}
+
+const CHANNEL_NAMES: [&str; 4] = ["R", "G", "B", "A"];
+
+#[derive(Clone, Copy, PartialEq)]
+struct DataKey {
+    mip: usize,
+    component: ComponentType,
+    channel: usize,
+    range: [f64; 2],
+    false_color: bool,
+}
+
+pub struct DataViewer {
+    id: egui::Id,
+    // 32-bit components can hold either integers or floats
+    as_float: bool,
+    channel: usize,
+    auto_range: bool,
+    range: [f64; 2],
+    false_color: bool,
+
+    range_key: Option<(usize, ComponentType, usize)>,
+    texture: Option<egui::TextureHandle>,
+    texture_key: Option<DataKey>,
+}
+
+// Polynomial approximation of the Turbo colormap
+fn false_color(t: f32) -> egui::Color32 {
+    let t = t.clamp(0.0, 1.0);
+    let r = 0.135721 + t * (4.615393 + t * (-42.660323 + t * (132.13109 + t * (-152.9424 + t * 59.28638))));
+    let g = 0.091403 + t * (2.194188 + t * (4.842967 + t * (-14.185033 + t * (4.277299 + t * 2.829566))));
+    let b = 0.106673 + t * (12.641946 + t * (-60.582048 + t * (110.36277 + t * (-89.90311 + t * 27.34825))));
+    let convert = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    egui::Color32::from_rgb(convert(r), convert(g), convert(b))
+}
+
+impl DataViewer {
+    pub fn new(id: egui::Id) -> Self {
+        Self {
+            id,
+            as_float: false,
+            channel: 0,
+            auto_range: true,
+            range: [0.0, 1.0],
+            false_color: true,
+
+            range_key: None,
+            texture: None,
+            texture_key: None,
+        }
+    }
+
+    // Rebuilds the texture and range on the next frame, for when the data changed under the same mip
+    pub fn invalidate(&mut self) {
+        self.range_key = None;
+        self.texture_key = None;
+    }
+
+    fn component(&self, texels: &DataTexels) -> ComponentType {
+        match texels.component {
+            ComponentType::U32 | ComponentType::F32 if self.as_float => ComponentType::F32,
+            ComponentType::U32 | ComponentType::F32 => ComponentType::U32,
+            ComponentType::U8 => ComponentType::U8,
+        }
+    }
+
+    fn normalize(&self, value: f64) -> f32 {
+        let [min, max] = self.range;
+        if max > min { ((value - min) / (max - min)) as f32 } else { 0.0 }
+    }
+
+    fn display_color(&self, value: f64) -> egui::Color32 {
+        let t = self.normalize(value);
+        match self.false_color {
+            true => false_color(t),
+            false => egui::Color32::from_gray((t.clamp(0.0, 1.0) * 255.0).round() as u8),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, texels: DataTexels, mip: usize, toasts: &mut egui_notify::Toasts) {
+        ui.horizontal(|ui| {
+            if texels.channels > 1 {
+                for (channel, name) in CHANNEL_NAMES.iter().enumerate().take(texels.channels) {
+                    ui.selectable_value(&mut self.channel, channel, *name);
+                }
+                ui.separator();
+            }
+            if texels.component != ComponentType::U8 {
+                ui.selectable_value(&mut self.as_float, false, "u32");
+                ui.selectable_value(&mut self.as_float, true, "f32");
+                ui.separator();
+            }
+            ui.checkbox(&mut self.false_color, "False color");
+            ui.checkbox(&mut self.auto_range, "Auto range");
+            ui.add_enabled(!self.auto_range, egui::DragValue::new(&mut self.range[0]).speed(0.01).prefix("Min: "));
+            ui.add_enabled(!self.auto_range, egui::DragValue::new(&mut self.range[1]).speed(0.01).prefix("Max: "));
+            ui.separator();
+            if ui.button("Export CSV…").clicked() {
+                match self.export_csv(&texels) {
+                    Ok(_) => {
+                        toasts.success("CSV exported successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                    },
+                    Err(e) => {
+                        toasts.error(format!("Failed to export CSV: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                    }
+                }
+            }
+        });
+        self.channel = self.channel.min(texels.channels.saturating_sub(1));
+
+        let component = self.component(&texels);
+        let range_key = (mip, component, self.channel);
+        if self.auto_range && self.range_key != Some(range_key) {
+            let values = (0..texels.height)
+                .flat_map(|y| (0..texels.width).map(move |x| (x, y)))
+                .filter_map(|(x, y)| texels.value(component, x, y, self.channel))
+                .filter(|value| value.is_finite());
+            let (min, max) = values.fold((f64::MAX, f64::MIN), |(min, max), value| (min.min(value), max.max(value)));
+            self.range = if min <= max { [min, max] } else { [0.0, 1.0] };
+            self.range_key = Some(range_key);
+        } else if !self.auto_range {
+            self.range_key = None;
+        }
+
+        let key = DataKey { mip, component, channel: self.channel, range: self.range, false_color: self.false_color };
+        if self.texture.is_none() || self.texture_key != Some(key) {
+            let pixels = (0..texels.height)
+                .flat_map(|y| (0..texels.width).map(move |x| (x, y)))
+                .map(|(x, y)| texels.value(component, x, y, self.channel).map(|value| self.display_color(value)).unwrap_or(egui::Color32::TRANSPARENT))
+                .collect();
+            let color_image = egui::ColorImage { size: [texels.width as usize, texels.height as usize], pixels };
+            self.texture = Some(ui.ctx().load_texture(format!("{:?}.data", self.id), color_image, egui::TextureOptions::NEAREST));
+            self.texture_key = Some(key);
+        }
+
+        ui.horizontal_top(|ui| {
+            if let Some(texture) = &self.texture {
+                let image_size = texture.size_vec2();
+                let scale = (256.0 / image_size.x.max(image_size.y)).max(1.0 / 16.0);
+                let (rect, _) = ui.allocate_exact_size(image_size * scale, egui::Sense::hover());
+                ui.painter().image(texture.id(), rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
+            }
+            ui.vertical(|ui| {
+                self.paint_grid(ui, &texels, component);
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}×{}, {} × {}", texels.width, texels.height, texels.channels, component.name()));
+            ui.label(format!("Range: {} – {}", self.range[0], self.range[1]));
+        });
+    }
+
+    // Only the visible cells are laid out, so large textures stay responsive
+    fn paint_grid(&self, ui: &mut egui::Ui, texels: &DataTexels, component: ComponentType) {
+        let font_id = egui::FontId::monospace(12.0);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font_id));
+        let cell_size = egui::vec2(88.0, row_height * texels.channels as f32 + 4.0);
+        let header_size = egui::vec2(40.0, row_height + 4.0);
+        let content_size = header_size + egui::vec2(cell_size.x * texels.width as f32, cell_size.y * texels.height as f32);
+
+        egui::ScrollArea::both()
+            .id_salt(self.id.with("grid"))
+            .max_height(512.0)
+            .auto_shrink([false, true])
+            .show_viewport(ui, |ui, viewport| {
+                let (rect, response) = ui.allocate_exact_size(content_size, egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                let origin = rect.min + header_size;
+                let first_column = ((viewport.min.x - header_size.x) / cell_size.x).floor().max(0.0) as u32;
+                let last_column = (((viewport.max.x - header_size.x) / cell_size.x).ceil().max(0.0) as u32).min(texels.width);
+                let first_row = ((viewport.min.y - header_size.y) / cell_size.y).floor().max(0.0) as u32;
+                let last_row = (((viewport.max.y - header_size.y) / cell_size.y).ceil().max(0.0) as u32).min(texels.height);
+
+                let text_color = ui.visuals().text_color();
+                for y in first_row..last_row {
+                    for x in first_column..last_column {
+                        let cell = egui::Rect::from_min_size(origin + egui::vec2(x as f32 * cell_size.x, y as f32 * cell_size.y), cell_size).shrink(1.0);
+                        let background = texels.value(component, x, y, self.channel).map(|value| self.display_color(value)).unwrap_or(egui::Color32::TRANSPARENT);
+                        painter.rect_filled(cell, 2.0, background.gamma_multiply(0.5));
+                        for channel in 0..texels.channels {
+                            let position = cell.left_top() + egui::vec2(4.0, 2.0 + channel as f32 * row_height);
+                            painter.text(position, egui::Align2::LEFT_TOP, texels.format_value(component, x, y, channel), font_id.clone(), text_color);
+                        }
+                    }
+                }
+
+                // Headers stick to the viewport edges
+                let header_color = ui.visuals().weak_text_color();
+                let header_background = ui.visuals().window_fill;
+                painter.rect_filled(egui::Rect::from_min_size(egui::pos2(rect.min.x + viewport.min.x, rect.min.y + viewport.min.y), egui::vec2(viewport.width(), header_size.y)), 0.0, header_background);
+                painter.rect_filled(egui::Rect::from_min_size(egui::pos2(rect.min.x + viewport.min.x, rect.min.y + viewport.min.y), egui::vec2(header_size.x, viewport.height())), 0.0, header_background);
+                for x in first_column..last_column {
+                    let position = egui::pos2(origin.x + (x as f32 + 0.5) * cell_size.x, rect.min.y + viewport.min.y + 2.0);
+                    painter.text(position, egui::Align2::CENTER_TOP, x.to_string(), font_id.clone(), header_color);
+                }
+                for y in first_row..last_row {
+                    let position = egui::pos2(rect.min.x + viewport.min.x + 4.0, origin.y + y as f32 * cell_size.y + 2.0);
+                    painter.text(position, egui::Align2::LEFT_TOP, y.to_string(), font_id.clone(), header_color);
+                }
+
+                if let Some(hover_position) = response.hover_pos() {
+                    let cell = (hover_position - origin) / cell_size;
+                    if cell.x >= 0.0 && cell.y >= 0.0 && (cell.x as u32) < texels.width && (cell.y as u32) < texels.height {
+                        let (x, y) = (cell.x as u32, cell.y as u32);
+                        let values = (0..texels.channels).map(|channel| format!("{}: {}", CHANNEL_NAMES[channel], texels.format_value(component, x, y, channel))).collect::<Vec<_>>().join("\n");
+                        response.on_hover_text(format!("({}, {})\n{}", x, y, values));
+                    }
+                }
+            });
+    }
+
+    fn export_csv(&self, texels: &DataTexels) -> Result<(), std::io::Error> {
+        let Some(output_path) = rfd::FileDialog::new().set_title("Export texel values").add_filter("CSV", &["csv"]).set_file_name("texels.csv").save_file() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Output path not found."));
+        };
+
+        let component = self.component(texels);
+        let mut output_file = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+        let columns = CHANNEL_NAMES.iter().take(texels.channels).map(|name| name.to_lowercase()).collect::<Vec<_>>().join(",");
+        writeln!(output_file, "x,y,{}", columns)?;
+        for y in 0..texels.height {
+            for x in 0..texels.width {
+                let values = (0..texels.channels).map(|channel| texels.value(component, x, y, channel).map(|value| value.to_string()).unwrap_or_default()).collect::<Vec<_>>().join(",");
+                writeln!(output_file, "{},{},{}", x, y, values)?;
+            }
+        }
+        output_file.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use eframe::egui;
+
+use crate::dictionary;
+
+#[derive(Default)]
+pub struct DictionaryWindow {
+    open: bool,
+    filter: String,
+}
+
+impl DictionaryWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, toasts: &mut egui_notify::Toasts) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(format!("{} Name dictionary", egui_phosphor::regular::BOOK_OPEN))
+            .id(egui::Id::new("name_dictionary"))
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Known names: {}", dictionary::len()));
+                ui.horizontal(|ui| {
+                    if ui.button("Load names…").clicked() {
+                        self.load(toasts);
+                    }
+                    if ui.button("Save names…").clicked()
+                        && let Some(path) = rfd::FileDialog::new().set_title("Save names").add_filter("Text", &["txt"]).set_file_name("names.txt").save_file() {
+                        match dictionary::save(&path) {
+                            Ok(_) => {
+                                toasts.success("Names saved successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            },
+                            Err(e) => {
+                                toasts.error(format!("Failed to save names: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            }
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                let unresolved = dictionary::unresolved();
+                ui.label(format!("Unresolved hashes: {}", unresolved.len()));
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    ui.text_edit_singleline(&mut self.filter);
+                });
+                let filter = self.filter.trim().to_uppercase();
+                let unresolved = unresolved.into_iter().map(|hash| format!("{:08X}", hash)).filter(|hash| hash.contains(&filter)).collect::<Vec<_>>();
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(unresolved.join("\n"));
+                    }
+                    if ui.button("Export…").clicked()
+                        && let Some(path) = rfd::FileDialog::new().set_title("Export unresolved hashes").add_filter("Text", &["txt"]).set_file_name("unresolved.txt").save_file() {
+                        match dictionary::save_unresolved(&path) {
+                            Ok(_) => {
+                                toasts.success("Unresolved hashes exported successfully.").duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            },
+                            Err(e) => {
+                                toasts.error(format!("Failed to export unresolved hashes: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                            }
+                        }
+                    }
+                });
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, row_height, unresolved.len(), |ui, row_range| {
+                        for hash in &unresolved[row_range] {
+                            ui.monospace(hash);
+                        }
+                    });
+            });
+        self.open = open;
+    }
+
+    fn load(&mut self, toasts: &mut egui_notify::Toasts) {
+        let Some(paths) = rfd::FileDialog::new().set_title("Load names").add_filter("Text", &["txt"]).pick_files() else {
+            return;
+        };
+
+        for path in paths {
+            match dictionary::load(&path) {
+                Ok(count) => {
+                    toasts.success(format!("Loaded {} new names from {}.", count, path.file_name().unwrap_or_default().to_str().unwrap_or_default())).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                },
+                Err(e) => {
+                    toasts.error(format!("Failed to load names: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                }
+            }
+        }
+    }
+}
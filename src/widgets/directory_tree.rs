@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use eframe::egui;
+
+use crate::util::format_size;
+
+#[derive(Default)]
+struct DirectoryNode {
+    // Full folder path with a trailing '/', empty for the root
+    path: String,
+    children: BTreeMap<String, DirectoryNode>,
+    file_count: usize,
+    compressed_size: u64,
+    uncompressed_size: u64,
+}
+
+impl DirectoryNode {
+    fn insert(&mut self, folders: &[&str], compressed_size: u64, uncompressed_size: u64) {
+        self.file_count += 1;
+        self.compressed_size += compressed_size;
+        self.uncompressed_size += uncompressed_size;
+
+        if let Some((folder, rest)) = folders.split_first() {
+            let path = format!("{}{}/", self.path, folder);
+            self.children.entry(folder.to_string()).or_insert_with(|| DirectoryNode { path, ..Default::default() }).insert(rest, compressed_size, uncompressed_size);
+        }
+    }
+}
+
+// Drops empty segments, so "/a/b" and "a//b" both end up as "a/b"
+fn normalize(path: &str) -> String {
+    path.split('/').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("/")
+}
+
+// Whether `path` is somewhere below `folder`, a folder path as the tree reports it
+pub fn in_folder(folder: &str, path: &str) -> bool {
+    folder.is_empty() || normalize(path).starts_with(folder)
+}
+
+pub enum DirectoryTreeAction {
+    Extract(String),
+}
+
+// Folder hierarchy of a flat list of '/'-separated paths, totals include every nested file
+pub struct DirectoryTree {
+    root: DirectoryNode,
+    selected: String,
+    reveal: Option<String>,
+}
+
+impl DirectoryTree {
+    pub fn new<'a>(files: impl Iterator<Item = (&'a str, u64, u64)>) -> Self {
+        Self {
+            root: Self::build(files),
+            selected: String::new(),
+            reveal: None,
+        }
+    }
+
+    // Rebuilds the folders, e.g. after names were resolved, keeping the selection
+    pub fn set_files<'a>(&mut self, files: impl Iterator<Item = (&'a str, u64, u64)>) {
+        self.root = Self::build(files);
+    }
+
+    fn build<'a>(files: impl Iterator<Item = (&'a str, u64, u64)>) -> DirectoryNode {
+        let mut root = DirectoryNode::default();
+        for (path, compressed_size, uncompressed_size) in files {
+            let parts = path.split('/').filter(|part| !part.is_empty()).collect::<Vec<_>>();
+            let folders = parts.split_last().map(|(_, folders)| folders).unwrap_or_default();
+            root.insert(folders, compressed_size, uncompressed_size);
+        }
+        root
+    }
+
+    // Folder prefix the file list should be limited to, empty when everything is shown
+    pub fn selected(&self) -> &str {
+        &self.selected
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        in_folder(&self.selected, path)
+    }
+
+    // Selects the folder holding `path` and expands the tree down to it
+    pub fn reveal(&mut self, path: &str) {
+        let path = normalize(path);
+        let folder = match path.rfind('/') {
+            Some(index) => &path[..=index],
+            None => "",
+        };
+        self.selected = folder.to_string();
+        self.reveal = Some(folder.to_string());
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, id: egui::Id) -> Option<DirectoryTreeAction> {
+        let mut action = None;
+        let reveal = self.reveal.take();
+        Self::show_node(ui, id, &self.root, "All files", &mut self.selected, reveal.as_deref(), &mut action);
+        action
+    }
+
+    fn show_node(ui: &mut egui::Ui, id: egui::Id, node: &DirectoryNode, name: &str, selected: &mut String, reveal: Option<&str>, action: &mut Option<DirectoryTreeAction>) {
+        let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id.with(&node.path), node.path.is_empty());
+        if let Some(reveal) = reveal && reveal.starts_with(&node.path) && reveal != node.path {
+            state.set_open(true);
+        }
+
+        let mut header = |ui: &mut egui::Ui| {
+            let is_selected = *selected == node.path;
+            let response = ui.selectable_label(is_selected, format!("{} {}", egui_phosphor::regular::FOLDER, name));
+            if response.clicked() {
+                *selected = node.path.clone();
+            }
+            if reveal == Some(node.path.as_str()) {
+                response.scroll_to_me(Some(egui::Align::Center));
+            }
+            ui.weak(format!("{} files, {} / {}", node.file_count, format_size(node.compressed_size), format_size(node.uncompressed_size)));
+            if ui.small_button(egui_phosphor::regular::TRAY_ARROW_UP).on_hover_text("Extract folder…").clicked() {
+                *action = Some(DirectoryTreeAction::Extract(node.path.clone()));
+            }
+        };
+
+        match node.children.is_empty() {
+            true => {
+                ui.horizontal(|ui| {
+                    ui.add_space(ui.spacing().indent);
+                    header(ui);
+                });
+            },
+            false => {
+                state.show_header(ui, header).body(|ui| {
+                    for (child_name, child) in node.children.iter() {
+                        Self::show_node(ui, id, child, child_name, selected, reveal, action);
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use eframe::egui;
+
+use crate::filter::Filter;
+
+const FILTER_HELP: &str = "Terms are separated by spaces and must all match, prefix a term with - to negate it.\n\
+text: path contains text (case-insensitive)\n\
+*.pack, ui/??: glob over the whole path\n\
+/regex/: regular expression\n\
+archive:data003, ext:.pack, hash:1234ABCD, compressed:false\n\
+size>1MB, csize<=64KB";
+
+// Text box that keeps the last valid filter while the current text fails to parse
+#[derive(Default)]
+pub struct FilterEdit {
+    text: String,
+    filter: Filter,
+    error: Option<String>,
+}
+
+impl FilterEdit {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filter.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.filter = Filter::default();
+        self.error = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let response = ui.add(egui::TextEdit::singleline(&mut self.text).hint_text("Filter…")).on_hover_text(FILTER_HELP);
+        if response.changed() {
+            match Filter::parse(&self.text) {
+                Ok(filter) => {
+                    self.filter = filter;
+                    self.error = None;
+                },
+                Err(e) => self.error = Some(e),
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, egui_phosphor::regular::WARNING).on_hover_text(error);
+        }
+        response
+    }
+}
@@ -0,0 +1,322 @@
+use eframe::egui;
+
+const BYTES_PER_ROW: usize = 16;
+const MIN_HEIGHT: f32 = 240.0;
+const MAX_HEIGHT: f32 = 480.0;
+// Stop collecting matches after this many, searching for a single common byte would otherwise list the whole file
+const MAX_MATCHES: usize = 10000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SearchMode {
+    Bytes,
+    Text,
+}
+
+impl SearchMode {
+    fn name(&self) -> &'static str {
+        match self {
+            SearchMode::Bytes => "Bytes",
+            SearchMode::Text => "Text",
+        }
+    }
+}
+
+// "0x1A0", "1A0h" or decimal
+fn parse_offset(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).or_else(|| text.strip_suffix('h')) {
+        return usize::from_str_radix(hex, 16).ok();
+    }
+    text.parse().ok()
+}
+
+// Hex bytes with optional spaces, "??" matches any byte
+fn parse_pattern(text: &str) -> Result<Vec<Option<u8>>, String> {
+    let digits = text.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.is_ascii() {
+        return Err("Expected pairs of hex digits, e.g. \"42 58 ?? 4E\".".to_string());
+    }
+    (0..digits.len()).step_by(2).map(|index| match &digits[index..index + 2] {
+        "??" => Ok(None),
+        pair => u8::from_str_radix(pair, 16).map(Some).map_err(|_| format!("\"{}\" is not a hex byte.", pair)),
+    }).collect()
+}
+
+fn find_all(data: &[u8], pattern: &[Option<u8>]) -> Vec<usize> {
+    if pattern.is_empty() || pattern.len() > data.len() {
+        return Vec::new();
+    }
+    data.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| window.iter().zip(pattern.iter()).all(|(byte, expected)| expected.is_none_or(|expected| *byte == expected)))
+        .map(|(offset, _)| offset)
+        .take(MAX_MATCHES)
+        .collect()
+}
+
+fn read_le<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+// Hex and ASCII dump with offset navigation, a selection inspector and pattern search.
+// Clicking selects a byte, shift-click extends the selection.
+pub struct HexViewer {
+    id_salt: &'static str,
+    selection: Option<(usize, usize)>,
+    goto: String,
+    search: String,
+    search_mode: SearchMode,
+    search_error: Option<String>,
+    pattern_len: usize,
+    matches: Vec<usize>,
+    current_match: usize,
+    scroll_to: Option<usize>,
+}
+
+impl HexViewer {
+    pub fn new(id_salt: &'static str) -> Self {
+        Self {
+            id_salt,
+            selection: None,
+            goto: String::new(),
+            search: String::new(),
+            search_mode: SearchMode::Bytes,
+            search_error: None,
+            pattern_len: 0,
+            matches: Vec::new(),
+            current_match: 0,
+            scroll_to: None,
+        }
+    }
+
+    fn select(&mut self, start: usize, len: usize) {
+        self.selection = Some((start, start + len.max(1) - 1));
+        self.scroll_to = Some(start);
+    }
+
+    fn selected_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        self.selection.map(|(anchor, end)| anchor.min(end)..=anchor.max(end))
+    }
+
+    fn search(&mut self, data: &[u8]) {
+        let pattern = match self.search_mode {
+            SearchMode::Bytes => parse_pattern(&self.search),
+            SearchMode::Text => Ok(self.search.bytes().map(Some).collect()),
+        };
+        match pattern {
+            Ok(pattern) => {
+                self.search_error = None;
+                self.pattern_len = pattern.len();
+                self.matches = find_all(data, &pattern);
+                self.current_match = 0;
+                if let Some(offset) = self.matches.first().copied() {
+                    self.select(offset, self.pattern_len);
+                }
+            },
+            Err(e) => self.search_error = Some(e),
+        }
+    }
+
+    fn step_match(&mut self, forward: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.current_match = match forward {
+            true => (self.current_match + 1) % self.matches.len(),
+            false => (self.current_match + self.matches.len() - 1) % self.matches.len(),
+        };
+        self.select(self.matches[self.current_match], self.pattern_len);
+    }
+
+    fn is_match(&self, offset: usize) -> bool {
+        // Matches are sorted, find the last one starting at or before the offset
+        let index = self.matches.partition_point(|start| *start <= offset);
+        index > 0 && offset < self.matches[index - 1] + self.pattern_len
+    }
+
+    fn paint_toolbar(&mut self, ui: &mut egui::Ui, data: &[u8]) {
+        ui.horizontal(|ui| {
+            ui.label("Go to:");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.goto).hint_text("0x0").desired_width(80.0));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                match parse_offset(&self.goto) {
+                    Some(offset) if offset < data.len() => self.select(offset, 1),
+                    _ => {},
+                }
+            }
+
+            ui.separator();
+
+            egui::ComboBox::from_id_salt((self.id_salt, "search_mode"))
+                .selected_text(self.search_mode.name())
+                .width(60.0)
+                .show_ui(ui, |ui| {
+                    for mode in [SearchMode::Bytes, SearchMode::Text] {
+                        ui.selectable_value(&mut self.search_mode, mode, mode.name());
+                    }
+                });
+            let hint = match self.search_mode {
+                SearchMode::Bytes => "42 58 4F 4E",
+                SearchMode::Text => "Text",
+            };
+            let response = ui.add(egui::TextEdit::singleline(&mut self.search).hint_text(hint).desired_width(160.0));
+            if (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) | ui.button(egui_phosphor::regular::MAGNIFYING_GLASS).clicked() {
+                self.search(data);
+            }
+            if let Some(error) = &self.search_error {
+                ui.colored_label(ui.visuals().error_fg_color, egui_phosphor::regular::WARNING).on_hover_text(error);
+            }
+            if ui.add_enabled(!self.matches.is_empty(), egui::Button::new(egui_phosphor::regular::CARET_UP)).clicked() {
+                self.step_match(false);
+            }
+            if ui.add_enabled(!self.matches.is_empty(), egui::Button::new(egui_phosphor::regular::CARET_DOWN)).clicked() {
+                self.step_match(true);
+            }
+            match self.matches.len() {
+                0 => ui.label("No matches"),
+                MAX_MATCHES => ui.label(format!("{}/{}+ matches", self.current_match + 1, MAX_MATCHES)),
+                count => ui.label(format!("{}/{} matches", self.current_match + 1, count)),
+            };
+        });
+    }
+
+    fn paint_inspector(&mut self, ui: &mut egui::Ui, data: &[u8]) {
+        let Some(range) = self.selected_range() else {
+            ui.weak("Click a byte to inspect it.");
+            return;
+        };
+        let offset = *range.start();
+        ui.label(format!("Offset: 0x{:X} ({})", offset, offset));
+        ui.label(format!("Selected: {} bytes", range.end() - range.start() + 1));
+        ui.separator();
+
+        let mut follow = None;
+        egui::Grid::new((self.id_salt, "inspector")).num_columns(2).striped(true).show(ui, |ui| {
+            let mut row = |name: &str, value: Option<String>| {
+                ui.label(name);
+                ui.monospace(value.unwrap_or_else(|| "-".to_string()));
+                ui.end_row();
+            };
+            row("u8", read_le::<1>(data, offset).map(|bytes| bytes[0].to_string()));
+            row("i8", read_le::<1>(data, offset).map(|bytes| (bytes[0] as i8).to_string()));
+            row("u16", read_le(data, offset).map(|bytes| u16::from_le_bytes(bytes).to_string()));
+            row("i16", read_le(data, offset).map(|bytes| i16::from_le_bytes(bytes).to_string()));
+            row("u32", read_le(data, offset).map(|bytes| u32::from_le_bytes(bytes).to_string()));
+            row("i32", read_le(data, offset).map(|bytes| i32::from_le_bytes(bytes).to_string()));
+            row("u64", read_le(data, offset).map(|bytes| u64::from_le_bytes(bytes).to_string()));
+            row("i64", read_le(data, offset).map(|bytes| i64::from_le_bytes(bytes).to_string()));
+            row("f32", read_le(data, offset).map(|bytes| f32::from_le_bytes(bytes).to_string()));
+            row("f64", read_le(data, offset).map(|bytes| f64::from_le_bytes(bytes).to_string()));
+
+            // Offsets in these formats are u32s relative to where they are stored
+            let target = read_le(data, offset).map(|bytes| offset as u64 + u32::from_le_bytes(bytes) as u64);
+            ui.label("Relative offset");
+            ui.horizontal(|ui| {
+                match target {
+                    Some(target) => ui.monospace(format!("0x{:X}", target)),
+                    None => ui.monospace("-"),
+                };
+                if ui.add_enabled(target.is_some_and(|target| target < data.len() as u64), egui::Button::new("Follow")).clicked() {
+                    follow = target;
+                }
+            });
+            ui.end_row();
+
+            let string = data.get(offset..).map(|rest| rest.iter().take_while(|byte| **byte != 0).take(64).map(|byte| *byte as char).collect::<String>());
+            ui.label("String");
+            ui.monospace(string.unwrap_or_default());
+            ui.end_row();
+        });
+
+        if let Some(target) = follow {
+            self.select(target as usize, 1);
+        }
+    }
+
+    fn byte_color(&self, ui: &egui::Ui, offset: usize, selected: &Option<std::ops::RangeInclusive<usize>>) -> egui::Color32 {
+        if selected.as_ref().is_some_and(|range| range.contains(&offset)) {
+            ui.visuals().selection.bg_fill
+        } else if self.is_match(offset) {
+            ui.visuals().warn_fg_color.gamma_multiply(0.3)
+        } else {
+            egui::Color32::TRANSPARENT
+        }
+    }
+
+    fn paint_rows(&mut self, ui: &mut egui::Ui, data: &[u8]) {
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let row_count = data.len().div_ceil(BYTES_PER_ROW);
+        let mut scroll_area = egui::ScrollArea::vertical().id_salt((self.id_salt, "rows")).auto_shrink([true, false]);
+        if let Some(offset) = self.scroll_to.take() {
+            let row = offset / BYTES_PER_ROW;
+            scroll_area = scroll_area.vertical_scroll_offset((row as f32 - 4.0).max(0.0) * (row_height + ui.spacing().item_spacing.y));
+        }
+
+        let selected = self.selected_range();
+        let mut clicked = None;
+        scroll_area.show_rows(ui, row_height, row_count, |ui, row_range| {
+            for row in row_range {
+                let start = row * BYTES_PER_ROW;
+                let bytes = &data[start..(start + BYTES_PER_ROW).min(data.len())];
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    ui.add(egui::Label::new(egui::RichText::new(format!("{:08X}  ", start)).monospace().weak()).selectable(false));
+                    for column in 0..BYTES_PER_ROW {
+                        let text = match bytes.get(column) {
+                            Some(byte) => format!("{:02X}", byte),
+                            None => "  ".to_string(),
+                        };
+                        let background = self.byte_color(ui, start + column, &selected);
+                        let response = ui.add(egui::Label::new(egui::RichText::new(text).monospace().background_color(background)).selectable(false).sense(egui::Sense::click()));
+                        if response.clicked() && column < bytes.len() {
+                            clicked = Some(start + column);
+                        }
+                        let gap = if column == BYTES_PER_ROW / 2 - 1 { "  " } else { " " };
+                        ui.add(egui::Label::new(egui::RichText::new(gap).monospace()).selectable(false));
+                    }
+                    ui.add(egui::Label::new(egui::RichText::new(" ").monospace()).selectable(false));
+                    for (column, byte) in bytes.iter().enumerate() {
+                        let character = match byte.is_ascii_graphic() || *byte == b' ' {
+                            true => *byte as char,
+                            false => '.',
+                        };
+                        let background = self.byte_color(ui, start + column, &selected);
+                        let response = ui.add(egui::Label::new(egui::RichText::new(character.to_string()).monospace().background_color(background)).selectable(false).sense(egui::Sense::click()));
+                        if response.clicked() {
+                            clicked = Some(start + column);
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(offset) = clicked {
+            self.selection = match (ui.input(|i| i.modifiers.shift), self.selection) {
+                (true, Some((anchor, _))) => Some((anchor, offset)),
+                _ => Some((offset, offset)),
+            };
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, data: &[u8]) {
+        if data.is_empty() {
+            ui.label("No data.");
+            return;
+        }
+
+        self.paint_toolbar(ui, data);
+        ui.separator();
+        // Managers are often painted inside a PACK's scroll area, so the rows get a bounded height of their own
+        let height = ui.available_height().clamp(MIN_HEIGHT, MAX_HEIGHT);
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_height(height);
+                self.paint_rows(ui, data);
+            });
+            ui.separator();
+            ui.vertical(|ui| {
+                self.paint_inspector(ui, data);
+            });
+        });
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use eframe::egui;
+
+use crate::index_diff::{self, ChangeKind, IndexChange, IndexDiff, Verification};
+use crate::vfs::{Metadata, Vfs};
+use crate::widgets::filter_edit::FilterEdit;
+use crate::widgets::table::SortableTable;
+
+// Filled in by the comparison running in the background
+type PendingDiff = Arc<Mutex<Option<Result<IndexDiff, std::io::Error>>>>;
+
+pub struct IndexDiffWindow {
+    open: bool,
+    old_path: Option<PathBuf>,
+    new_path: Option<PathBuf>,
+    verify: bool,
+    pending: Option<PendingDiff>,
+    diff: Option<IndexDiff>,
+    shown_kinds: HashSet<ChangeKind>,
+    filter: FilterEdit,
+    table: SortableTable,
+}
+
+impl Default for IndexDiffWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            old_path: None,
+            new_path: None,
+            verify: false,
+            pending: None,
+            diff: None,
+            shown_kinds: ChangeKind::ALL.into_iter().collect(),
+            filter: FilterEdit::default(),
+            table: SortableTable::new("index_diff", &["Change", "Path", "Hash", "Old Archive", "Old Offset", "Old Size", "New Archive", "New Offset", "New Size"]).with_hidden(&[4, 7]),
+        }
+    }
+}
+
+fn side_text(metadata: &Option<Metadata>, column: usize) -> String {
+    let Some(metadata) = metadata else {
+        return String::new();
+    };
+    match column {
+        0 => metadata.archive.clone(),
+        1 => format!("{:X}", metadata.offset),
+        _ => metadata.size.to_string(),
+    }
+}
+
+fn cell_text(change: &IndexChange, column: usize) -> String {
+    match column {
+        0 => match change.verification {
+            Verification::Unverified => format!("{} (unverified)", change.kind.name()),
+            _ => change.kind.name().to_string(),
+        },
+        1 => change.path.clone(),
+        2 => format!("{:08X}", change.hash),
+        3..=5 => side_text(&change.old, column - 3),
+        _ => side_text(&change.new, column - 6),
+    }
+}
+
+fn compare_side(a: &Option<Metadata>, b: &Option<Metadata>, column: usize) -> std::cmp::Ordering {
+    match column {
+        0 => a.as_ref().map(|metadata| &metadata.archive).cmp(&b.as_ref().map(|metadata| &metadata.archive)),
+        1 => a.as_ref().map(|metadata| metadata.offset).cmp(&b.as_ref().map(|metadata| metadata.offset)),
+        _ => a.as_ref().map(|metadata| metadata.size).cmp(&b.as_ref().map(|metadata| metadata.size)),
+    }
+}
+
+fn pick_index(title: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new().set_title(title).add_filter("Index", &["arc"]).pick_file()
+}
+
+impl IndexDiffWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    // Mounting and verifying read whole archives, so the comparison runs off the UI thread
+    fn compare(&mut self, runtime: &tokio::runtime::Handle) {
+        let (Some(old_path), Some(new_path)) = (self.old_path.clone(), self.new_path.clone()) else {
+            return;
+        };
+        let verify = self.verify;
+        let pending = PendingDiff::default();
+        self.pending = Some(pending.clone());
+        runtime.spawn_blocking(move || {
+            let result = Vfs::mount(&old_path).and_then(|old| {
+                let new = Vfs::mount(&new_path)?;
+                let mut diff = IndexDiff::compare(&old, &new);
+                if verify {
+                    diff.verify_moved(&old, &new);
+                }
+                Ok(diff)
+            });
+            *pending.lock().unwrap() = Some(result);
+        });
+    }
+
+    fn poll_pending(&mut self, ctx: &egui::Context, toasts: &mut egui_notify::Toasts) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        let Some(result) = pending.lock().unwrap().take() else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        };
+        self.pending = None;
+        match result {
+            Ok(diff) => {
+                toasts.success(diff.summary()).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+                self.diff = Some(diff);
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to compare indexes: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    fn export(changes: &[&IndexChange], csv: bool, toasts: &mut egui_notify::Toasts) {
+        let (name, extension) = match csv {
+            true => ("CSV", "csv"),
+            false => ("JSON", "json"),
+        };
+        let Some(path) = rfd::FileDialog::new().set_title("Export changes").add_filter(name, &[extension]).set_file_name(format!("changes.{}", extension)).save_file() else {
+            return;
+        };
+
+        let result = match csv {
+            true => Ok(index_diff::to_csv(changes)),
+            false => index_diff::to_json(changes),
+        }.and_then(|report| std::fs::write(&path, report));
+        match result {
+            Ok(_) => {
+                toasts.success(format!("Exported {} changes.", changes.len())).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            },
+            Err(e) => {
+                toasts.error(format!("Failed to export changes: {}", e)).duration(Some(std::time::Duration::from_secs(10))).closable(true);
+            }
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, runtime: &tokio::runtime::Handle, toasts: &mut egui_notify::Toasts) {
+        self.poll_pending(ctx, toasts);
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(format!("{} Compare indexes", egui_phosphor::regular::GIT_DIFF))
+            .id(egui::Id::new("index_diff"))
+            .open(&mut open)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("index_diff_paths").num_columns(2).show(ui, |ui| {
+                    for (label, title, path) in [("Old index:", "Select old info.arc", &mut self.old_path), ("New index:", "Select new info.arc", &mut self.new_path)] {
+                        ui.label(label);
+                        ui.horizontal(|ui| {
+                            if ui.button("Browse…").clicked() && let Some(picked) = pick_index(title) {
+                                *path = Some(picked);
+                            }
+                            match path {
+                                Some(path) => ui.label(path.to_str().unwrap_or_default()),
+                                None => ui.weak("None"),
+                            };
+                        });
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let ready = self.old_path.is_some() && self.new_path.is_some() && self.pending.is_none();
+                    if ui.add_enabled(ready, egui::Button::new("Compare")).clicked() {
+                        self.compare(runtime);
+                    }
+                    ui.checkbox(&mut self.verify, "Verify moved files").on_hover_text("Reads moved files from both installs to confirm their data is the same. Needs the archives next to each info.arc.");
+                    if self.pending.is_some() {
+                        ui.spinner();
+                        ui.label("Comparing…");
+                    }
+                });
+
+                let Some(diff) = &self.diff else {
+                    return;
+                };
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    for kind in ChangeKind::ALL {
+                        let mut shown = self.shown_kinds.contains(&kind);
+                        if ui.checkbox(&mut shown, format!("{} ({})", kind.name(), diff.count(kind))).changed() {
+                            match shown {
+                                true => self.shown_kinds.insert(kind),
+                                false => self.shown_kinds.remove(&kind),
+                            };
+                        }
+                    }
+                    ui.label(format!("Unchanged: {}", diff.unchanged));
+                });
+
+                let mut changes = diff.changes.iter()
+                    .filter(|change| self.shown_kinds.contains(&change.kind))
+                    .filter(|change| self.filter.filter().matches(&change.metadata().filter_entry()))
+                    .collect::<Vec<_>>();
+
+                ui.horizontal(|ui| {
+                    self.filter.show(ui);
+                    ui.label(format!("{} of {} changes", changes.len(), diff.changes.len()));
+                    if ui.button("Export JSON…").clicked() {
+                        Self::export(&changes, false, toasts);
+                    }
+                    if ui.button("Export CSV…").clicked() {
+                        Self::export(&changes, true, toasts);
+                    }
+                });
+
+                self.table.sort(&mut changes, |a, b, column| match column {
+                    0 => a.kind.cmp(&b.kind),
+                    1 => a.path.cmp(&b.path),
+                    2 => a.hash.cmp(&b.hash),
+                    3..=5 => compare_side(&a.old, &b.old, column - 3),
+                    _ => compare_side(&a.new, &b.new, column - 6),
+                });
+                self.table.show(
+                    ui,
+                    changes.len(),
+                    None,
+                    |_| false,
+                    |index, column| cell_text(changes[index], column),
+                    |ui, index, column| {
+                        if column == 2 {
+                            ui.style_mut().override_font_id = Some(egui::FontId::monospace(12.0));
+                        }
+                        ui.add(egui::Label::new(cell_text(changes[index], column)).wrap_mode(egui::TextWrapMode::Extend));
+                    },
+                );
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,11 @@
+pub mod batch_export_dialog;
+pub mod data_viewer;
+pub mod dictionary_window;
+pub mod directory_tree;
+pub mod filter_edit;
+pub mod hex_viewer;
+pub mod index_diff_window;
+pub mod table;
+pub mod texture_compare;
+pub mod texture_viewer;
+pub mod virtual_list;
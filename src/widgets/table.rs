@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+use eframe::egui;
+
+struct TableColumn {
+    name: &'static str,
+    visible: bool,
+    sortable: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct SortKey {
+    column: usize,
+    descending: bool,
+}
+
+pub struct TableResponse {
+    // Index into the rows as shown, i.e. after sorting
+    pub clicked_row: Option<usize>,
+    pub modifiers: egui::Modifiers,
+    // The sort keys changed this frame, callers that cache the sorted rows should sort again
+    pub sort_changed: bool,
+}
+
+// Listing with clickable headings: a click sorts by that column and toggles the direction,
+// shift-click adds it as a secondary key. Right-clicking a heading toggles columns and right-clicking a row copies it.
+pub struct SortableTable {
+    id_salt: &'static str,
+    columns: Vec<TableColumn>,
+    sort_keys: Vec<SortKey>,
+}
+
+impl SortableTable {
+    pub fn new(id_salt: &'static str, columns: &[&'static str]) -> Self {
+        Self {
+            id_salt,
+            columns: columns.iter().map(|name| TableColumn { name, visible: true, sortable: true }).collect(),
+            sort_keys: Vec::new(),
+        }
+    }
+
+    // Columns holding widgets rather than values
+    pub fn with_unsortable(mut self, columns: &[usize]) -> Self {
+        for column in columns {
+            self.columns[*column].sortable = false;
+        }
+        self
+    }
+
+    pub fn with_hidden(mut self, columns: &[usize]) -> Self {
+        for column in columns {
+            self.columns[*column].visible = false;
+        }
+        self
+    }
+
+    // Earlier sort keys take priority, ties keep the order `rows` came in
+    pub fn sort<T>(&self, rows: &mut [T], compare: impl Fn(&T, &T, usize) -> Ordering) {
+        if self.sort_keys.is_empty() {
+            return;
+        }
+        rows.sort_by(|a, b| {
+            for key in self.sort_keys.iter() {
+                let ordering = match key.descending {
+                    true => compare(b, a, key.column),
+                    false => compare(a, b, key.column),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+    }
+
+    fn click_heading(&mut self, column: usize, add: bool) {
+        match self.sort_keys.iter().position(|key| key.column == column) {
+            Some(index) if add || self.sort_keys.len() == 1 => {
+                self.sort_keys[index].descending = !self.sort_keys[index].descending;
+            },
+            Some(index) => {
+                let key = self.sort_keys[index];
+                self.sort_keys = vec![SortKey { column, descending: !key.descending }];
+            },
+            None if add => self.sort_keys.push(SortKey { column, descending: false }),
+            None => self.sort_keys = vec![SortKey { column, descending: false }],
+        }
+    }
+
+    fn heading_text(&self, column: usize) -> String {
+        let name = self.columns[column].name;
+        let Some(index) = self.sort_keys.iter().position(|key| key.column == column) else {
+            return name.to_string();
+        };
+        let arrow = match self.sort_keys[index].descending {
+            true => egui_phosphor::regular::CARET_DOWN,
+            false => egui_phosphor::regular::CARET_UP,
+        };
+        match self.sort_keys.len() {
+            1 => format!("{} {}", name, arrow),
+            _ => format!("{} {}{}", name, arrow, index + 1),
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        row_count: usize,
+        scroll_to_row: Option<usize>,
+        is_selected: impl Fn(usize) -> bool,
+        row_text: impl Fn(usize, usize) -> String,
+        mut cell: impl FnMut(&mut egui::Ui, usize, usize),
+    ) -> TableResponse {
+        let previous_sort_keys = self.sort_keys.clone();
+        let visible_columns = self.columns.iter().enumerate().filter(|(_, column)| column.visible).map(|(index, _)| index).collect::<Vec<_>>();
+        let mut clicked_heading = None;
+        let mut toggled_column = None;
+        let mut clicked_row = None;
+
+        let mut table = egui_extras::TableBuilder::new(ui)
+            .id_salt(self.id_salt)
+            .striped(true)
+            .resizable(true)
+            .sense(egui::Sense::click())
+            .columns(egui_extras::Column::auto(), visible_columns.len());
+        if let Some(row) = scroll_to_row {
+            table = table.scroll_to_row(row, Some(egui::Align::Center));
+        }
+
+        table
+            .header(16.0, |mut header| {
+                for column in visible_columns.iter().copied() {
+                    header.col(|ui| {
+                        ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+                        let response = ui.add(egui::Label::new(egui::RichText::new(self.heading_text(column)).heading()).sense(egui::Sense::click()));
+                        if response.clicked() && self.columns[column].sortable {
+                            clicked_heading = Some((column, ui.input(|i| i.modifiers.shift)));
+                        }
+                        response.context_menu(|ui| {
+                            for (index, other) in self.columns.iter().enumerate() {
+                                let mut visible = other.visible;
+                                let name = if other.name.is_empty() { "(unnamed)" } else { other.name };
+                                if ui.checkbox(&mut visible, name).changed() {
+                                    toggled_column = Some(index);
+                                }
+                            }
+                            if ui.button("Clear sorting").clicked() {
+                                clicked_heading = None;
+                                toggled_column = None;
+                                self.sort_keys.clear();
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, row_count, |mut row| {
+                    let index = row.index();
+                    row.set_selected(is_selected(index));
+                    for column in visible_columns.iter().copied() {
+                        row.col(|ui| {
+                            cell(ui, index, column);
+                        });
+                    }
+
+                    let response = row.response();
+                    if response.clicked() {
+                        clicked_row = Some(index);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Copy row").clicked() {
+                            let text = visible_columns.iter().map(|column| row_text(index, *column)).collect::<Vec<_>>().join("\t");
+                            ui.ctx().copy_text(text);
+                            ui.close_menu();
+                        }
+                    });
+                });
+            });
+
+        if let Some((column, add)) = clicked_heading {
+            self.click_heading(column, add);
+        }
+        // Keep at least one column on screen
+        if let Some(column) = toggled_column && (!self.columns[column].visible || visible_columns.len() > 1) {
+            self.columns[column].visible = !self.columns[column].visible;
+        }
+
+        TableResponse {
+            clicked_row,
+            modifiers: ui.input(|i| i.modifiers),
+            sort_changed: self.sort_keys != previous_sort_keys,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use eframe::egui;
+
+use crate::texture::compare::{self, CompareSlot, TextureSnapshot};
+
+#[derive(Clone, Copy, PartialEq)]
+enum CompareMode {
+    SideBySide,
+    Swipe,
+    Onion,
+    Difference,
+}
+
+impl CompareMode {
+    const ALL: [CompareMode; 4] = [CompareMode::SideBySide, CompareMode::Swipe, CompareMode::Onion, CompareMode::Difference];
+
+    fn name(&self) -> &'static str {
+        match self {
+            CompareMode::SideBySide => "Side by side",
+            CompareMode::Swipe => "Swipe",
+            CompareMode::Onion => "Onion skin",
+            CompareMode::Difference => "Difference",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct CompareKey {
+    a: usize,
+    b: usize,
+    mip: usize,
+    gain: f32,
+}
+
+struct CompareTextures {
+    key: CompareKey,
+    a: egui::TextureHandle,
+    b: egui::TextureHandle,
+    difference: egui::TextureHandle,
+    max: u8,
+    mean: f32,
+    differing_pixels: usize,
+}
+
+pub struct TextureCompareWindow {
+    open: bool,
+    mode: CompareMode,
+    mip: usize,
+    swipe: f32,
+    opacity: f32,
+    gain: f32,
+
+    textures: Option<CompareTextures>,
+}
+
+impl Default for TextureCompareWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            mode: CompareMode::SideBySide,
+            mip: 0,
+            swipe: 0.5,
+            opacity: 0.5,
+            gain: 1.0,
+
+            textures: None,
+        }
+    }
+}
+
+fn load_texture(ctx: &egui::Context, name: &str, image: &image::RgbaImage) -> egui::TextureHandle {
+    let color_image = egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw());
+    ctx.load_texture(name, color_image, egui::TextureOptions::NEAREST)
+}
+
+impl TextureCompareWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if compare::take_open_request(ctx) {
+            self.open = true;
+        }
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(format!("{} Compare textures", egui_phosphor::regular::SWAP))
+            .id(egui::Id::new("texture_compare_window"))
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                let a = compare::get_slot(ctx, CompareSlot::A);
+                let b = compare::get_slot(ctx, CompareSlot::B);
+                for (slot, snapshot) in [(CompareSlot::A, &a), (CompareSlot::B, &b)] {
+                    ui.horizontal(|ui| {
+                        ui.strong(format!("{}:", slot.name()));
+                        match snapshot {
+                            Some(snapshot) => {
+                                ui.label(format!("{} ({}×{}, {})", snapshot.label, snapshot.width, snapshot.height, snapshot.format));
+                                if ui.small_button(egui_phosphor::regular::X).clicked() {
+                                    compare::clear_slot(ctx, slot);
+                                }
+                            },
+                            None => {
+                                ui.weak("Use \"Compare as\" on a texture preview to fill this slot.");
+                            }
+                        }
+                    });
+                }
+
+                let (Some(a), Some(b)) = (a, b) else {
+                    self.textures = None;
+                    return;
+                };
+
+                ui.separator();
+                self.paint_header_differences(ui, &a, &b);
+                ui.separator();
+                self.paint_comparison(ui, &a, &b);
+            });
+        self.open = open;
+    }
+
+    fn paint_header_differences(&self, ui: &mut egui::Ui, a: &TextureSnapshot, b: &TextureSnapshot) {
+        let differences = compare::header_differences(a, b);
+        if differences.is_empty() {
+            ui.label(format!("{} Headers match.", egui_phosphor::regular::CHECK));
+            return;
+        }
+
+        egui::Grid::new("texture_compare_headers")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Field");
+                ui.strong("A");
+                ui.strong("B");
+                ui.end_row();
+                for (field, value_a, value_b) in differences {
+                    ui.label(field);
+                    ui.label(value_a);
+                    ui.label(value_b);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn paint_comparison(&mut self, ui: &mut egui::Ui, a: &Arc<TextureSnapshot>, b: &Arc<TextureSnapshot>) {
+        let mip_count = a.mips.len().min(b.mips.len());
+        if mip_count == 0 {
+            ui.label("No decoded mips to compare.");
+            return;
+        }
+        self.mip = self.mip.min(mip_count - 1);
+
+        ui.horizontal(|ui| {
+            ui.label("Mip:");
+            ui.add(egui::Slider::new(&mut self.mip, 0..=(mip_count - 1)));
+            ui.separator();
+            for mode in CompareMode::ALL {
+                ui.selectable_value(&mut self.mode, mode, mode.name());
+            }
+        });
+        match self.mode {
+            CompareMode::SideBySide => {},
+            CompareMode::Swipe => {
+                ui.add(egui::Slider::new(&mut self.swipe, 0.0..=1.0).text("Position"));
+            },
+            CompareMode::Onion => {
+                ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0).text("B opacity"));
+            },
+            CompareMode::Difference => {
+                ui.add(egui::Slider::new(&mut self.gain, 1.0..=64.0).logarithmic(true).text("Gain"));
+            },
+        }
+
+        let key = CompareKey {
+            a: Arc::as_ptr(a) as usize,
+            b: Arc::as_ptr(b) as usize,
+            mip: self.mip,
+            gain: self.gain,
+        };
+        if self.textures.as_ref().is_none_or(|textures| textures.key != key) {
+            let image_a = &a.mips[self.mip];
+            let image_b = &b.mips[self.mip];
+            let difference = compare::difference(image_a, image_b, self.gain);
+            self.textures = Some(CompareTextures {
+                key,
+                a: load_texture(ui.ctx(), "texture_compare.a", image_a),
+                b: load_texture(ui.ctx(), "texture_compare.b", image_b),
+                difference: load_texture(ui.ctx(), "texture_compare.difference", &difference.heatmap),
+                max: difference.max,
+                mean: difference.mean,
+                differing_pixels: difference.differing_pixels,
+            });
+        }
+        let Some(textures) = &self.textures else {
+            return;
+        };
+
+        let pixel_count = (a.mips[self.mip].width() as usize * a.mips[self.mip].height() as usize).max(1);
+        ui.label(format!("Differing pixels: {} ({:.2}%), max delta: {}, mean delta: {:.3}", textures.differing_pixels, textures.differing_pixels as f32 * 100.0 / pixel_count as f32, textures.max, textures.mean));
+        if a.mips[self.mip].dimensions() != b.mips[self.mip].dimensions() {
+            ui.colored_label(ui.visuals().warn_fg_color, "Mip sizes differ, B is scaled to A.");
+        }
+
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let image_size = textures.a.size_vec2();
+        let columns = if self.mode == CompareMode::SideBySide { 2.0 } else { 1.0 };
+        let width = (ui.available_width() - ui.spacing().item_spacing.x * (columns - 1.0)) / columns;
+        let scale = (width / image_size.x).min(512.0 / image_size.y);
+        let size = image_size * scale;
+
+        match self.mode {
+            CompareMode::SideBySide => {
+                ui.horizontal(|ui| {
+                    for texture in [&textures.a, &textures.b] {
+                        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                        ui.painter().image(texture.id(), rect, uv, egui::Color32::WHITE);
+                    }
+                });
+            },
+            CompareMode::Swipe => {
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    self.swipe = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                }
+                // A is shown left of the divider, B to the right
+                let split = rect.left() + rect.width() * self.swipe;
+                ui.painter().image(textures.b.id(), rect, uv, egui::Color32::WHITE);
+                let left = egui::Rect::from_min_max(rect.min, egui::pos2(split, rect.bottom()));
+                ui.painter().with_clip_rect(left).image(textures.a.id(), rect, uv, egui::Color32::WHITE);
+                ui.painter().vline(split, rect.y_range(), ui.visuals().widgets.active.fg_stroke);
+            },
+            CompareMode::Onion => {
+                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                ui.painter().image(textures.a.id(), rect, uv, egui::Color32::WHITE);
+                ui.painter().image(textures.b.id(), rect, uv, egui::Color32::WHITE.gamma_multiply(self.opacity));
+            },
+            CompareMode::Difference => {
+                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                ui.painter().image(textures.difference.id(), rect, uv, egui::Color32::WHITE);
+            },
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use eframe::egui;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ToneMapping {
+    Clamp,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMapping {
+    fn name(&self) -> &'static str {
+        match self {
+            ToneMapping::Clamp => "Clamp",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::Aces => "ACES",
+        }
+    }
+
+    fn apply(&self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match self {
+            ToneMapping::Clamp => value.min(1.0),
+            ToneMapping::Reinhard => value / (1.0 + value),
+            ToneMapping::Aces => ((value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ViewerImage<'a> {
+    Ldr(&'a image::RgbaImage),
+    Hdr(&'a image::Rgba32FImage),
+}
+
+impl ViewerImage<'_> {
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ViewerImage::Ldr(image) => image.dimensions(),
+            ViewerImage::Hdr(image) => image.dimensions(),
+        }
+    }
+}
+
+pub struct RawTexels<'a> {
+    pub data: &'a [u8],
+    pub block_dimension: u32,
+    pub bytes_per_block: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct DisplayKey {
+    mip: usize,
+    channels: [bool; 4],
+    color_space: ColorSpace,
+    exposure: f32,
+    tone_mapping: ToneMapping,
+}
+
+pub struct TextureViewer {
+    id: egui::Id,
+    zoom: Option<f32>,
+    pan: egui::Vec2,
+    channels: [bool; 4],
+    color_space: ColorSpace,
+    exposure: f32,
+    tone_mapping: ToneMapping,
+
+    texture: Option<egui::TextureHandle>,
+    texture_key: Option<DisplayKey>,
+    checkerboard: Option<egui::TextureHandle>,
+}
+
+impl TextureViewer {
+    pub fn new(id: egui::Id) -> Self {
+        Self {
+            id,
+            zoom: None,
+            pan: egui::Vec2::ZERO,
+            channels: [true; 4],
+            color_space: ColorSpace::Srgb,
+            exposure: 0.0,
+            tone_mapping: ToneMapping::Clamp,
+
+            texture: None,
+            texture_key: None,
+            checkerboard: None,
+        }
+    }
+
+    // Rebuilds the texture on the next frame, for when the image changed under the same mip
+    pub fn invalidate(&mut self) {
+        self.texture_key = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, image: ViewerImage, mip: usize, raw: Option<RawTexels>) {
+        let (width, height) = image.dimensions();
+        let image_size = egui::Vec2::new(width as f32, height as f32);
+
+        ui.horizontal(|ui| {
+            for (i, label) in ["R", "G", "B", "A"].iter().enumerate() {
+                ui.toggle_value(&mut self.channels[i], *label);
+            }
+            ui.separator();
+            match image {
+                ViewerImage::Ldr(_) => {
+                    ui.selectable_value(&mut self.color_space, ColorSpace::Srgb, "sRGB");
+                    ui.selectable_value(&mut self.color_space, ColorSpace::Linear, "Linear");
+                },
+                ViewerImage::Hdr(_) => {
+                    ui.label("Exposure:");
+                    ui.add(egui::Slider::new(&mut self.exposure, -16.0..=16.0).step_by(0.25).suffix(" EV"));
+                    egui::ComboBox::from_id_salt(self.id.with("tone_mapping"))
+                        .selected_text(self.tone_mapping.name())
+                        .show_ui(ui, |ui| {
+                            for tone_mapping in [ToneMapping::Clamp, ToneMapping::Reinhard, ToneMapping::Aces] {
+                                ui.selectable_value(&mut self.tone_mapping, tone_mapping, tone_mapping.name());
+                            }
+                        });
+                }
+            }
+            ui.separator();
+            if ui.button("Fit").clicked() {
+                self.zoom = None;
+                self.pan = egui::Vec2::ZERO;
+            }
+            if ui.button("1:1").clicked() {
+                self.zoom = Some(1.0 / ui.ctx().pixels_per_point());
+            }
+        });
+
+        let key = DisplayKey { mip, channels: self.channels, color_space: self.color_space, exposure: self.exposure, tone_mapping: self.tone_mapping };
+        if self.texture.is_none() || self.texture_key != Some(key) {
+            let pixels = match image {
+                ViewerImage::Ldr(image) => image.pixels().map(|pixel| self.display_ldr_color(pixel.0)).collect(),
+                ViewerImage::Hdr(image) => image.pixels().map(|pixel| self.display_hdr_color(pixel.0)).collect(),
+            };
+            let color_image = egui::ColorImage { size: [width as usize, height as usize], pixels };
+            self.texture = Some(ui.ctx().load_texture(format!("{:?}.texture", self.id), color_image, egui::TextureOptions::NEAREST));
+            self.texture_key = Some(key);
+        }
+        let checkerboard = self.checkerboard.get_or_insert_with(|| {
+            let light = egui::Color32::from_gray(204);
+            let dark = egui::Color32::from_gray(153);
+            let color_image = egui::ColorImage { size: [2, 2], pixels: vec![light, dark, dark, light] };
+            ui.ctx().load_texture(format!("{:?}.checkerboard", self.id), color_image, egui::TextureOptions::NEAREST_REPEAT)
+        }).id();
+
+        let viewport_size = egui::Vec2::new(ui.available_width().max(256.0), 512.0);
+        let (viewport, response) = ui.allocate_exact_size(viewport_size, egui::Sense::click_and_drag());
+
+        let fit_zoom = (viewport.width() / image_size.x).min(viewport.height() / image_size.y);
+        let mut zoom = self.zoom.unwrap_or(fit_zoom);
+
+        if response.dragged() {
+            self.pan += response.drag_delta();
+        }
+        if response.double_clicked() {
+            self.zoom = None;
+            self.pan = egui::Vec2::ZERO;
+            zoom = fit_zoom;
+        }
+        if let Some(hover_position) = response.hover_pos() {
+            let scroll = ui.input_mut(|i| std::mem::take(&mut i.smooth_scroll_delta)).y;
+            if scroll != 0.0 {
+                let factor = (scroll / 200.0).exp();
+                let new_zoom = (zoom * factor).clamp(0.01, 256.0);
+                let from_center = hover_position - viewport.center() - self.pan;
+                self.pan += from_center - from_center * (new_zoom / zoom);
+                zoom = new_zoom;
+                self.zoom = Some(zoom);
+            }
+        }
+
+        let image_rect = egui::Rect::from_center_size(viewport.center() + self.pan, image_size * zoom);
+        let painter = ui.painter_at(viewport);
+        painter.rect_filled(viewport, 0.0, ui.visuals().extreme_bg_color);
+        if self.channels[3] && self.channels[..3].iter().any(|channel| *channel) {
+            let checker_uv = egui::Rect::from_min_size(egui::Pos2::ZERO, image_rect.size() / 16.0);
+            painter.image(checkerboard, image_rect, checker_uv, egui::Color32::WHITE);
+        }
+        if let Some(texture) = &self.texture {
+            painter.image(texture.id(), image_rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}×{}", width, height));
+            ui.label(format!("Zoom: {:.0}%", zoom * ui.ctx().pixels_per_point() * 100.0));
+
+            let Some(hover_position) = response.hover_pos() else {
+                return;
+            };
+            let texel = (hover_position - image_rect.min) / zoom;
+            if texel.x < 0.0 || texel.y < 0.0 || texel.x >= image_size.x || texel.y >= image_size.y {
+                return;
+            }
+            let (x, y) = (texel.x as u32, texel.y as u32);
+
+            ui.separator();
+            ui.style_mut().override_font_id = Some(egui::FontId::monospace(12.0));
+            ui.label(format!("({}, {})", x, y));
+            match image {
+                ViewerImage::Ldr(image) => {
+                    let [r, g, b, a] = image.get_pixel(x, y).0;
+                    ui.label(format!("RGBA: {} {} {} {}", r, g, b, a));
+                    ui.label(format!("({:.3} {:.3} {:.3} {:.3})", r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0));
+                },
+                ViewerImage::Hdr(image) => {
+                    let [r, g, b, a] = image.get_pixel(x, y).0;
+                    ui.label(format!("RGBA: {:.4} {:.4} {:.4} {:.4}", r, g, b, a));
+                }
+            }
+
+            if let Some(raw) = raw {
+                let blocks_per_row = width.div_ceil(raw.block_dimension);
+                let block_index = (y / raw.block_dimension) * blocks_per_row + x / raw.block_dimension;
+                let start = (block_index * raw.bytes_per_block) as usize;
+                if let Some(bytes) = raw.data.get(start..start + raw.bytes_per_block as usize) {
+                    let hex = bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+                    if raw.block_dimension > 1 {
+                        ui.label(format!("Block ({}, {}): {}", x / raw.block_dimension, y / raw.block_dimension, hex));
+                    } else {
+                        ui.label(format!("Raw: {}", hex));
+                    }
+                }
+            }
+        });
+    }
+
+    fn display_ldr_color(&self, [r, g, b, a]: [u8; 4]) -> egui::Color32 {
+        let convert = |value: u8| match self.color_space {
+            ColorSpace::Srgb => value,
+            ColorSpace::Linear => egui::ecolor::gamma_u8_from_linear_f32(value as f32 / 255.0),
+        };
+        self.display_color([convert(r), convert(g), convert(b)], a)
+    }
+
+    fn display_hdr_color(&self, [r, g, b, a]: [f32; 4]) -> egui::Color32 {
+        let scale = self.exposure.exp2();
+        let convert = |value: f32| egui::ecolor::gamma_u8_from_linear_f32(self.tone_mapping.apply(value * scale));
+        self.display_color([convert(r), convert(g), convert(b)], (a.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    fn display_color(&self, [r, g, b]: [u8; 3], a: u8) -> egui::Color32 {
+        let [show_r, show_g, show_b, show_a] = self.channels;
+        let selected = [(show_r, r), (show_g, g), (show_b, b)].into_iter().filter(|(show, _)| *show).map(|(_, value)| value).collect::<Vec<_>>();
+
+        match selected.as_slice() {
+            [] if show_a => egui::Color32::from_gray(a),
+            [value] => egui::Color32::from_rgba_unmultiplied(*value, *value, *value, if show_a { a } else { 255 }),
+            _ => {
+                let channel = |show: bool, value: u8| if show { value } else { 0 };
+                egui::Color32::from_rgba_unmultiplied(channel(show_r, r), channel(show_g, g), channel(show_b, b), if show_a { a } else { 255 })
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+
+// Scrolling list of variable height entries that only lays out the entries in view.
+// Entries that have not been shown yet are assumed to be `default_height` tall until they are.
+pub struct VirtualList {
+    id_salt: &'static str,
+    default_height: f32,
+    heights: HashMap<usize, f32>,
+}
+
+impl VirtualList {
+    pub fn new(id_salt: &'static str, default_height: f32) -> Self {
+        Self {
+            id_salt,
+            default_height,
+            heights: HashMap::new(),
+        }
+    }
+
+    fn height(&self, entry: usize) -> f32 {
+        self.heights.get(&entry).copied().unwrap_or(self.default_height)
+    }
+
+    // `entries` are stable keys, e.g. indices into the unfiltered list, so heights survive filtering
+    pub fn show(&mut self, ui: &mut egui::Ui, entries: &[usize], mut add_entry: impl FnMut(&mut egui::Ui, usize)) {
+        egui::ScrollArea::vertical()
+            .id_salt(self.id_salt)
+            .auto_shrink([false, true])
+            .show_viewport(ui, |ui, viewport| {
+                let spacing = ui.spacing().item_spacing.y;
+                let origin = ui.max_rect().min;
+                let width = ui.available_width();
+                let mut resized = false;
+
+                let mut y = 0.0;
+                for entry in entries.iter().copied() {
+                    let height = self.height(entry);
+                    if y + height >= viewport.min.y && y <= viewport.max.y {
+                        let rect = egui::Rect::from_min_size(origin + egui::vec2(0.0, y), egui::vec2(width, height));
+                        let mut entry_ui = ui.new_child(egui::UiBuilder::new().max_rect(rect).id_salt(entry).layout(egui::Layout::top_down(egui::Align::Min)));
+                        add_entry(&mut entry_ui, entry);
+
+                        let actual_height = entry_ui.min_rect().height();
+                        if (actual_height - height).abs() > 0.5 {
+                            self.heights.insert(entry, actual_height);
+                            resized = true;
+                        }
+                    }
+                    y += self.height(entry) + spacing;
+                }
+
+                ui.allocate_rect(egui::Rect::from_min_size(origin, egui::vec2(width, y)), egui::Sense::hover());
+                // Entries below a resized one moved, lay them out again at their new position
+                if resized {
+                    ui.ctx().request_repaint();
+                }
+            });
+    }
+}